@@ -0,0 +1,158 @@
+// AquaFlow Security Alerts - Alert codes and structured SecurityAlert details
+// Monitoring decodes `details` with AlertDetails::decode instead of parsing strings
+
+use stylus_sdk::alloy_primitives::U256;
+
+/// Pool id used in alert details when the alert is not tied to a pool
+pub const NO_POOL: U256 = U256::MAX;
+
+/// Encoded details length: observed, threshold, pool_id, block_number (32 bytes each)
+pub const DETAILS_LEN: usize = 128;
+
+/// Defined security alert codes (emitted as uint8)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AlertCode {
+    DailyVolumeExceeded = 1,
+    SuspiciousLargeAmount = 2,
+    CircuitBreakerTriggered = 3,
+}
+
+impl AlertCode {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(AlertCode::DailyVolumeExceeded),
+            2 => Some(AlertCode::SuspiciousLargeAmount),
+            3 => Some(AlertCode::CircuitBreakerTriggered),
+            _ => None,
+        }
+    }
+
+    /// Legacy alert_type string kept in the event for existing consumers
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertCode::DailyVolumeExceeded => "DAILY_VOLUME_EXCEEDED",
+            AlertCode::SuspiciousLargeAmount => "SUSPICIOUS_LARGE_AMOUNT",
+            AlertCode::CircuitBreakerTriggered => "CIRCUIT_BREAKER_TRIGGERED",
+        }
+    }
+
+    /// Severity from 1 (informational) to 5 (critical)
+    pub fn severity(&self) -> u8 {
+        match self {
+            AlertCode::DailyVolumeExceeded => 2,
+            AlertCode::SuspiciousLargeAmount => 3,
+            AlertCode::CircuitBreakerTriggered => 5,
+        }
+    }
+}
+
+/// Structured payload carried in SecurityAlert.details
+/// Layout: [observed: 32][threshold: 32][pool_id: 32][block_number: 32], big-endian
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AlertDetails {
+    pub observed: U256,     // Value that tripped the check
+    pub threshold: U256,    // Configured limit it was compared against
+    pub pool_id: U256,      // Pool involved, or NO_POOL
+    pub block_number: U256, // Block the alert was raised in
+}
+
+impl AlertDetails {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(DETAILS_LEN);
+        data.extend_from_slice(&self.observed.to_be_bytes::<32>());
+        data.extend_from_slice(&self.threshold.to_be_bytes::<32>());
+        data.extend_from_slice(&self.pool_id.to_be_bytes::<32>());
+        data.extend_from_slice(&self.block_number.to_be_bytes::<32>());
+        data
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, &'static str> {
+        if data.len() != DETAILS_LEN {
+            return Err("Invalid alert details length");
+        }
+
+        let word = |i: usize| U256::from_be_slice(&data[i * 32..(i + 1) * 32]);
+
+        Ok(Self {
+            observed: word(0),
+            threshold: word(1),
+            pool_id: word(2),
+            block_number: word(3),
+        })
+    }
+}
+
+/// Revert data for an intent rejected by a security check: the reason, then the alert code and encoded details
+/// The SecurityAlert log is discarded with the reverted transaction, so monitoring decodes the revert data instead
+pub fn rejection(reason: &str, code: AlertCode, details: &AlertDetails) -> Vec<u8> {
+    let mut data = Vec::with_capacity(reason.len() + 1 + DETAILS_LEN);
+    data.extend_from_slice(reason.as_bytes());
+    data.push(code as u8);
+    data.extend_from_slice(&details.encode());
+    data
+}
+
+/// Alert carried in revert data built by `rejection`
+pub fn decode_rejection(data: &[u8]) -> Result<(AlertCode, AlertDetails), &'static str> {
+    if data.len() < 1 + DETAILS_LEN {
+        return Err("Invalid rejection length");
+    }
+
+    let split = data.len() - DETAILS_LEN;
+    let code = AlertCode::from_u8(data[split - 1]).ok_or("Unknown alert code")?;
+    Ok((code, AlertDetails::decode(&data[split..])?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alert_code_roundtrip() {
+        for code in [
+            AlertCode::DailyVolumeExceeded,
+            AlertCode::SuspiciousLargeAmount,
+            AlertCode::CircuitBreakerTriggered,
+        ] {
+            assert_eq!(AlertCode::from_u8(code as u8), Some(code));
+        }
+
+        assert_eq!(AlertCode::from_u8(0), None);
+    }
+
+    #[test]
+    fn test_details_roundtrip() {
+        let details = AlertDetails {
+            observed: U256::from(1500),
+            threshold: U256::from(1000),
+            pool_id: NO_POOL,
+            block_number: U256::from(123456),
+        };
+
+        let encoded = details.encode();
+        assert_eq!(encoded.len(), DETAILS_LEN);
+        assert_eq!(AlertDetails::decode(&encoded), Ok(details));
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_length() {
+        assert!(AlertDetails::decode(&[0u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_rejection_roundtrip() {
+        let details = AlertDetails {
+            observed: U256::from(2500),
+            threshold: U256::from(2000),
+            pool_id: NO_POOL,
+            block_number: U256::from(42),
+        };
+
+        let data = rejection("Circuit breaker triggered", AlertCode::CircuitBreakerTriggered, &details);
+        assert!(data.starts_with(b"Circuit breaker triggered"));
+        assert_eq!(decode_rejection(&data), Ok((AlertCode::CircuitBreakerTriggered, details)));
+
+        assert!(decode_rejection(b"Circuit breaker triggered").is_err());
+    }
+}
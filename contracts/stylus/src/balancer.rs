@@ -0,0 +1,458 @@
+// AquaFlow Balancer Adapter - Weighted pools (80/20, multi-token) read through the Vault
+// Quotes use the weighted-product formula with 18-decimal fixed-point ln/exp
+
+use stylus_sdk::{
+    alloy_primitives::{Address, B256, U256},
+    prelude::*,
+    call::Call,
+};
+
+use crate::pool_adapter::{self, PoolAdapter, PoolSnapshot, PoolView, SwapCall, SwapParams, SwapPayment, PRICE_PRECISION};
+
+/// PoolInfo.pool_type for Balancer weighted pools
+pub const POOL_TYPE: u8 = 3;
+
+/// Balancer rejects swaps above 30% of the input balance or 30% of the output balance
+pub const MAX_IN_RATIO_BPS: u64 = 3000;
+pub const MAX_OUT_RATIO_BPS: u64 = 3000;
+
+/// 1.0 in 18-decimal fixed point
+pub const ONE: u64 = 1_000_000_000_000_000_000;
+
+/// ln(2) in 18-decimal fixed point
+const LN_2: u64 = 693_147_180_559_945_309;
+
+const BPS_DENOMINATOR: u64 = 10000;
+const SERIES_TERMS: u64 = 40;
+const STATE_WORDS: usize = 5;
+const SWAP_KIND_GIVEN_IN: u64 = 0;
+
+sol_interface! {
+    interface IBalancerWeightedPool {
+        function getPoolId() external view returns (bytes32);
+        function getVault() external view returns (address);
+        function getNormalizedWeights() external view returns (uint256[]);
+    }
+
+    interface IBalancerVault {
+        function getPoolTokens(bytes32 poolId) external view returns (address[] tokens, uint256[] balances, uint256 lastChangeBlock);
+    }
+}
+
+/// Weighted pool parameters for the registry's token_a/token_b pair
+/// Balances of the pair live in PoolInfo.reserve_a/reserve_b
+#[derive(SolidityType, Clone, Debug, PartialEq, Eq)]
+pub struct BalancerPoolState {
+    pub balancer_pool_id: B256, // Vault pool id
+    pub vault: Address,
+    pub n_tokens: u8,
+    pub weight_a: U256,         // Normalized weight of token_a (1e18 = 100%)
+    pub weight_b: U256,
+}
+
+/// Pool snapshot read from chain
+pub struct BalancerSnapshot {
+    pub state: BalancerPoolState,
+    pub reserve_a: U256,
+    pub reserve_b: U256,
+}
+
+/// -ln(x) for 0 < x <= 1, all in 18-decimal fixed point
+pub fn neg_ln(x: U256) -> Result<U256, &'static str> {
+    let one = U256::from(ONE);
+    if x == U256::ZERO || x > one {
+        return Err("ln input out of range");
+    }
+
+    // Scale into [1, 2): x * 2^k = m, so -ln(x) = k * ln2 - ln(m)
+    let mut m = x;
+    let mut k = 0u64;
+    while m < one {
+        m <<= 1;
+        k += 1;
+    }
+
+    // ln(m) = 2 * atanh(z) with z = (m - 1) / (m + 1) <= 1/3
+    let z = (m - one) * one / (m + one);
+    let z_squared = z * z / one;
+    let mut term = z;
+    let mut series = U256::ZERO;
+    for n in 0..SERIES_TERMS {
+        series += term / U256::from(2 * n + 1);
+        term = term * z_squared / one;
+        if term == U256::ZERO {
+            break;
+        }
+    }
+
+    Ok(U256::from(k) * U256::from(LN_2) - series * U256::from(2))
+}
+
+/// e^(-v) for v >= 0, all in 18-decimal fixed point
+pub fn exp_neg(v: U256) -> U256 {
+    let one = U256::from(ONE);
+    let ln_2 = U256::from(LN_2);
+
+    // e^(-v) = 2^(-k) * e^(-r) with r in [0, ln2)
+    let k = v / ln_2;
+    if k >= U256::from(128) {
+        return U256::ZERO;
+    }
+    let r = v - k * ln_2;
+
+    // e^r by Taylor series, then invert
+    let mut term = one;
+    let mut exp_r = one;
+    for n in 1..SERIES_TERMS {
+        term = term * r / (one * U256::from(n));
+        if term == U256::ZERO {
+            break;
+        }
+        exp_r += term;
+    }
+
+    (one * one / exp_r) >> k.to::<usize>()
+}
+
+/// base^exponent for 0 < base <= 1 and exponent >= 0, in 18-decimal fixed point
+pub fn pow_fraction(base: U256, exponent: U256) -> Result<U256, &'static str> {
+    let log = neg_ln(base)?;
+    let product = log.checked_mul(exponent).ok_or("pow overflow")? / U256::from(ONE);
+    Ok(exp_neg(product))
+}
+
+/// Weighted-product exact-input quote:
+/// out = balance_out * (1 - (balance_in / (balance_in + amount_in_after_fee)) ^ (weight_in / weight_out))
+pub fn quote_exact_in(
+    balance_in: U256,
+    weight_in: U256,
+    balance_out: U256,
+    weight_out: U256,
+    amount_in: U256,
+    fee_bps: U256
+) -> Result<U256, &'static str> {
+    if balance_in == U256::ZERO || balance_out == U256::ZERO {
+        return Err("Insufficient liquidity");
+    }
+
+    if weight_in == U256::ZERO || weight_out == U256::ZERO {
+        return Err("Invalid weights");
+    }
+
+    if fee_bps >= U256::from(BPS_DENOMINATOR) {
+        return Err("Invalid fee");
+    }
+
+    if amount_in * U256::from(BPS_DENOMINATOR) > balance_in * U256::from(MAX_IN_RATIO_BPS) {
+        return Err("Max in ratio exceeded");
+    }
+
+    let one = U256::from(ONE);
+    let amount_in_after_fee = amount_in * (U256::from(BPS_DENOMINATOR) - fee_bps) / U256::from(BPS_DENOMINATOR);
+    let base = balance_in * one / (balance_in + amount_in_after_fee);
+    let exponent = weight_in * one / weight_out;
+
+    // Round the power up (1 wei) so the quote never overstates output
+    let power = core::cmp::min(pow_fraction(base, exponent)? + U256::from(1), one);
+    Ok(balance_out * (one - power) / one)
+}
+
+/// Weighted-product exact-output quote:
+/// in = balance_in * ((balance_out / (balance_out - amount_out)) ^ (weight_out / weight_in) - 1) / (1 - fee)
+pub fn quote_exact_out(
+    balance_in: U256,
+    weight_in: U256,
+    balance_out: U256,
+    weight_out: U256,
+    amount_out: U256,
+    fee_bps: U256
+) -> Result<U256, &'static str> {
+    if balance_in == U256::ZERO || balance_out == U256::ZERO {
+        return Err("Insufficient liquidity");
+    }
+
+    if weight_in == U256::ZERO || weight_out == U256::ZERO {
+        return Err("Invalid weights");
+    }
+
+    if fee_bps >= U256::from(BPS_DENOMINATOR) {
+        return Err("Invalid fee");
+    }
+
+    if amount_out * U256::from(BPS_DENOMINATOR) > balance_out * U256::from(MAX_OUT_RATIO_BPS) {
+        return Err("Max out ratio exceeded");
+    }
+
+    let one = U256::from(ONE);
+    let base = (balance_out - amount_out) * one / balance_out;
+    let exponent = weight_out * one / weight_in;
+
+    // Every step rounds toward a larger input so the quote never understates it
+    let power = pow_fraction(base, exponent)?;
+    if power == U256::ZERO {
+        return Err("Insufficient liquidity");
+    }
+    let ratio = (one * one + power - U256::from(1)) / power;
+    let amount_in_after_fee = (balance_in * (ratio - one) + one - U256::from(1)) / one;
+
+    let fee_factor = U256::from(BPS_DENOMINATOR) - fee_bps;
+    Ok((amount_in_after_fee * U256::from(BPS_DENOMINATOR) + fee_factor - U256::from(1)) / fee_factor)
+}
+
+/// Marginal price before fees: (balance_out / weight_out) / (balance_in / weight_in), in PRICE_PRECISION units
+pub fn spot_price(balance_in: U256, weight_in: U256, balance_out: U256, weight_out: U256) -> Result<U256, &'static str> {
+    if balance_in == U256::ZERO || weight_out == U256::ZERO {
+        return Err("Insufficient liquidity");
+    }
+    Ok(balance_out * weight_in * U256::from(PRICE_PRECISION) / (balance_in * weight_out))
+}
+
+/// Registry state words of a Balancer pool, in BalancerPoolState field order
+pub fn encode_state(state: &BalancerPoolState) -> Vec<U256> {
+    vec![
+        U256::from_be_bytes(state.balancer_pool_id.0),
+        pool_adapter::address_word(state.vault),
+        U256::from(state.n_tokens),
+        state.weight_a,
+        state.weight_b,
+    ]
+}
+
+/// BalancerPoolState stored by encode_state
+pub fn decode_state(words: &[U256]) -> Result<BalancerPoolState, &'static str> {
+    if words.len() != STATE_WORDS || words[2] > U256::from(u8::MAX) {
+        return Err("Invalid pool state");
+    }
+
+    Ok(BalancerPoolState {
+        balancer_pool_id: B256::from(words[0].to_be_bytes::<32>()),
+        vault: pool_adapter::word_address(words[1]),
+        n_tokens: words[2].to::<u8>(),
+        weight_a: words[3],
+        weight_b: words[4],
+    })
+}
+
+/// Read vault balances and normalized weights for a weighted pool
+/// Reverting or non-conforming contracts are reported as errors
+pub fn fetch_state(pool_address: Address, token_a: Address, token_b: Address) -> Result<BalancerSnapshot, Vec<u8>> {
+    let pool = IBalancerWeightedPool::new(pool_address);
+
+    let balancer_pool_id = pool.get_pool_id(Call::new())
+        .map_err(|_| b"Pool id unavailable".to_vec())?;
+    let vault = pool.get_vault(Call::new())
+        .map_err(|_| b"Pool vault unavailable".to_vec())?;
+    let weights = pool.get_normalized_weights(Call::new())
+        .map_err(|_| b"Pool weights unavailable".to_vec())?;
+
+    let (tokens, balances, _) = IBalancerVault::new(vault)
+        .get_pool_tokens(Call::new(), balancer_pool_id)
+        .map_err(|_| b"Pool tokens unavailable".to_vec())?;
+
+    if tokens.len() != weights.len() || tokens.len() != balances.len() {
+        return Err(b"Pool weights mismatch".to_vec());
+    }
+
+    let index_a = tokens.iter().position(|token| *token == token_a);
+    let index_b = tokens.iter().position(|token| *token == token_b);
+    let (index_a, index_b) = match (index_a, index_b) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return Err(b"Pool tokens mismatch".to_vec()),
+    };
+
+    Ok(BalancerSnapshot {
+        state: BalancerPoolState {
+            balancer_pool_id,
+            vault,
+            n_tokens: tokens.len() as u8,
+            weight_a: weights[index_a],
+            weight_b: weights[index_b],
+        },
+        reserve_a: balances[index_a],
+        reserve_b: balances[index_b],
+    })
+}
+
+/// Adapter for Balancer weighted pools; swaps go through the pool's vault
+pub struct BalancerAdapter;
+
+impl BalancerAdapter {
+    /// (weight_in, weight_out) for the swap direction
+    fn weights(pool: &PoolView, a_to_b: bool) -> Result<(U256, U256), &'static str> {
+        let state = decode_state(pool.state)?;
+        Ok(if a_to_b { (state.weight_a, state.weight_b) } else { (state.weight_b, state.weight_a) })
+    }
+}
+
+impl PoolAdapter for BalancerAdapter {
+    fn pool_type(&self) -> u8 {
+        POOL_TYPE
+    }
+
+    fn fetch_state(&self, pool_address: Address, token_a: Address, token_b: Address) -> Result<PoolSnapshot, Vec<u8>> {
+        let snapshot = fetch_state(pool_address, token_a, token_b)?;
+        Ok(PoolSnapshot {
+            reserve_a: snapshot.reserve_a,
+            reserve_b: snapshot.reserve_b,
+            state: encode_state(&snapshot.state),
+        })
+    }
+
+    fn quote_exact_in(&self, pool: &PoolView, a_to_b: bool, amount_in: U256) -> Result<U256, &'static str> {
+        let (weight_in, weight_out) = Self::weights(pool, a_to_b)?;
+        let (balance_in, balance_out) = pool.reserves(a_to_b);
+        quote_exact_in(balance_in, weight_in, balance_out, weight_out, amount_in, pool.fee_bps)
+    }
+
+    fn quote_exact_out(&self, pool: &PoolView, a_to_b: bool, amount_out: U256) -> Result<U256, &'static str> {
+        let (weight_in, weight_out) = Self::weights(pool, a_to_b)?;
+        let (balance_in, balance_out) = pool.reserves(a_to_b);
+        quote_exact_out(balance_in, weight_in, balance_out, weight_out, amount_out, pool.fee_bps)
+    }
+
+    fn spot_price(&self, pool: &PoolView, a_to_b: bool) -> Result<U256, &'static str> {
+        let (weight_in, weight_out) = Self::weights(pool, a_to_b)?;
+        let (balance_in, balance_out) = pool.reserves(a_to_b);
+        spot_price(balance_in, weight_in, balance_out, weight_out)
+    }
+
+    /// Every pool token, held by the vault rather than the pool contract
+    fn holdings(&self, pool: &PoolView) -> Result<Vec<(Address, U256)>, Vec<u8>> {
+        let state = decode_state(pool.state).map_err(|e| e.as_bytes().to_vec())?;
+        let (tokens, balances, _) = IBalancerVault::new(state.vault)
+            .get_pool_tokens(Call::new(), state.balancer_pool_id)
+            .map_err(|_| b"Pool tokens unavailable".to_vec())?;
+
+        Ok(tokens.into_iter().zip(balances).collect())
+    }
+
+    fn payment(&self) -> SwapPayment {
+        SwapPayment::Allowance
+    }
+
+    /// vault.swap(singleSwap, funds, limit, deadline) with a GIVEN_IN single swap
+    /// `swap.sender` must be the caller or a relayer it approved on the vault
+    fn encode_swap(&self, pool: &PoolView, swap: &SwapParams) -> Result<SwapCall, &'static str> {
+        let state = decode_state(pool.state)?;
+        let (token_in, token_out) = pool.tokens(swap.a_to_b);
+
+        Ok(SwapCall {
+            target: state.vault,
+            calldata: pool_adapter::encode_call(
+                "swap((bytes32,uint8,address,address,uint256,bytes),(address,bool,address,bool),uint256,uint256)",
+                &[
+                    // Head: singleSwap offset, funds (inline), limit, deadline
+                    U256::from(224),
+                    pool_adapter::address_word(swap.sender),
+                    U256::ZERO,
+                    pool_adapter::address_word(swap.recipient),
+                    U256::ZERO,
+                    swap.min_amount_out,
+                    swap.deadline,
+                    // singleSwap: poolId, kind, assetIn, assetOut, amount, userData offset, empty userData
+                    U256::from_be_bytes(state.balancer_pool_id.0),
+                    U256::from(SWAP_KIND_GIVEN_IN),
+                    pool_adapter::address_word(token_in),
+                    pool_adapter::address_word(token_out),
+                    swap.amount_in,
+                    U256::from(192),
+                    U256::ZERO,
+                ],
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixed(value: f64) -> U256 {
+        U256::from((value * 1e18) as u128)
+    }
+
+    fn to_f64(value: U256) -> f64 {
+        value.to::<u128>() as f64 / 1e18
+    }
+
+    #[test]
+    fn test_ln_and_exp_match_floating_point() {
+        for x in [1.0, 0.999, 0.8, 0.5, 0.2, 0.01, 1e-9] {
+            let ln = to_f64(neg_ln(fixed(x)).unwrap());
+            assert!((ln + x.ln()).abs() < 1e-12, "ln({x})");
+        }
+
+        for v in [0.0, 0.1, 0.693, 1.0, 5.0, 20.0] {
+            let exp = to_f64(exp_neg(fixed(v)));
+            assert!((exp - (-v).exp()).abs() < 1e-12, "exp(-{v})");
+        }
+
+        assert_eq!(neg_ln(U256::ZERO), Err("ln input out of range"));
+        assert_eq!(neg_ln(fixed(1.5)), Err("ln input out of range"));
+    }
+
+    #[test]
+    fn test_equal_weights_match_constant_product() {
+        let balance = fixed(1_000_000.0);
+        let amount = fixed(1_000.0);
+        let half = fixed(0.5);
+
+        let out = quote_exact_in(balance, half, balance, half, amount, U256::ZERO).unwrap();
+        let constant_product = amount * balance / (balance + amount);
+        let diff = if out > constant_product { out - constant_product } else { constant_product - out };
+        assert!(diff < fixed(0.000001));
+    }
+
+    #[test]
+    fn test_eighty_twenty_pool() {
+        // 80% token_in, 20% token_out, equal value balances: 800k / 200k
+        let balance_in = fixed(800_000.0);
+        let balance_out = fixed(200_000.0);
+        let amount = fixed(10_000.0);
+
+        let out = quote_exact_in(balance_in, fixed(0.8), balance_out, fixed(0.2), amount, U256::from(30)).unwrap();
+
+        let after_fee = 10_000.0 * 0.997;
+        let expected = 200_000.0 * (1.0 - (800_000.0f64 / (800_000.0 + after_fee)).powf(4.0));
+        assert!((to_f64(out) - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_exact_out_inverts_quote() {
+        let (balance_in, balance_out) = (fixed(800_000.0), fixed(200_000.0));
+        let (weight_in, weight_out) = (fixed(0.8), fixed(0.2));
+        let amount_out = fixed(2_500.0);
+
+        let amount_in = quote_exact_out(balance_in, weight_in, balance_out, weight_out, amount_out, U256::from(30)).unwrap();
+        let out = quote_exact_in(balance_in, weight_in, balance_out, weight_out, amount_in, U256::from(30)).unwrap();
+        assert!(out >= amount_out);
+        assert!(out - amount_out < fixed(0.000001));
+    }
+
+    #[test]
+    fn test_spot_price_and_state_words() {
+        // 80/20 pool holding equal value: 1 token_in is worth 1 token_out at the margin
+        let price = spot_price(fixed(800_000.0), fixed(0.8), fixed(200_000.0), fixed(0.2)).unwrap();
+        assert_eq!(price, U256::from(ONE));
+
+        let state = BalancerPoolState {
+            balancer_pool_id: B256::from([5u8; 32]),
+            vault: Address::from([6u8; 20]),
+            n_tokens: 3,
+            weight_a: fixed(0.5),
+            weight_b: fixed(0.3),
+        };
+        assert_eq!(decode_state(&encode_state(&state)), Ok(state));
+    }
+
+    #[test]
+    fn test_max_in_ratio() {
+        let balance = fixed(1_000.0);
+        let half = fixed(0.5);
+        assert!(quote_exact_in(balance, half, balance, half, fixed(300.0), U256::ZERO).is_ok());
+        assert_eq!(
+            quote_exact_in(balance, half, balance, half, fixed(301.0), U256::ZERO),
+            Err("Max in ratio exceeded")
+        );
+    }
+}
@@ -0,0 +1,407 @@
+// AquaFlow Batch Auctions - CoW-style settlement of intents collected in a batch window
+// Every order in a batch clears at one uniform price; only the net imbalance is routed through the pool
+
+use stylus_sdk::{
+    alloy_primitives::{Address, U256},
+    prelude::*,
+};
+
+use crate::secure_router::ValidatedIntent;
+
+/// Maximum intents per batch (bounds settlement gas)
+pub const MAX_BATCH_ORDERS: u64 = 50;
+
+/// Longest configurable batch window in seconds
+pub const MAX_BATCH_WINDOW: u64 = 3600;
+
+const BPS_DENOMINATOR: u64 = 10000;
+
+/// Clearing prices are reported with 18 decimals
+const PRICE_PRECISION: u64 = 1_000_000_000_000_000_000; // 1e18
+
+/// Time after a batch closes for authorized settlement before anyone can reclaim its orders
+pub const BATCH_SETTLEMENT_GRACE: u64 = 3600;
+
+/// Bisection steps when sizing the pool leg (precision of excess / 2^64)
+const MAX_CLEARING_STEPS: u32 = 64;
+
+/// Batch order status
+pub const BATCH_ORDER_PENDING: u8 = 0;
+pub const BATCH_ORDER_FILLED: u8 = 1;
+pub const BATCH_ORDER_REFUNDED: u8 = 2;
+
+/// Batch of intents on one pair (token0 < token1)
+#[derive(SolidityType, Clone)]
+pub struct Batch {
+    pub token0: Address,
+    pub token1: Address,
+    pub end_time: U256,    // Submissions close and settlement opens at this timestamp
+    pub order_count: U256,
+    pub settled: bool,
+}
+
+/// Intent escrowed in a batch
+#[derive(SolidityType, Clone)]
+pub struct BatchOrder {
+    pub intent: ValidatedIntent,
+    pub batch_id: U256,
+    pub amount_out: U256, // Net amount delivered once filled
+    pub status: u8,       // BATCH_ORDER_*
+}
+
+/// Whether a pending order can be refunded by anyone instead of waiting for settlement:
+/// once its deadline has passed, or once the batch has gone unsettled for BATCH_SETTLEMENT_GRACE
+pub fn is_refundable(now: U256, end_time: U256, deadline: U256) -> bool {
+    now > deadline || now >= end_time + U256::from(BATCH_SETTLEMENT_GRACE)
+}
+
+/// Clearing input for one order
+#[derive(Clone, Copy, Debug)]
+pub struct ClearingOrder {
+    pub amount_in: U256,
+    pub min_amount_out: U256,
+    pub sells_token0: bool,
+    pub eligible: bool, // False for expired or otherwise rejected orders
+}
+
+/// Result of clearing a batch
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clearing {
+    pub payouts: Vec<U256>,       // Gross payout per order, zero if excluded
+    pub included: Vec<bool>,
+    pub price_token0: U256,       // Uniform clearing price: price_token1 token1 per price_token0 token0
+    pub price_token1: U256,
+    pub matched_token0: U256,     // token0 exchanged peer-to-peer
+    pub matched_token1: U256,     // token1 exchanged peer-to-peer
+    pub pool_amount_in: U256,     // Net imbalance routed through the pool
+    pub pool_sells_token0: bool,
+    pub pool_amount_out: U256,    // Quoted pool output; the payouts rely on receiving at least this
+}
+
+impl Clearing {
+    /// Clearing that fills nothing
+    pub fn unfilled(order_count: usize) -> Self {
+        Clearing {
+            payouts: vec![U256::ZERO; order_count],
+            included: vec![false; order_count],
+            price_token0: U256::ZERO,
+            price_token1: U256::ZERO,
+            matched_token0: U256::ZERO,
+            matched_token1: U256::ZERO,
+            pool_amount_in: U256::ZERO,
+            pool_sells_token0: true,
+            pool_amount_out: U256::ZERO,
+        }
+    }
+
+    /// Clearing price in token1 per token0, scaled by 1e18 (zero if nothing cleared)
+    pub fn price(&self) -> U256 {
+        if self.price_token0 == U256::ZERO {
+            return U256::ZERO;
+        }
+        self.price_token1 * U256::from(PRICE_PRECISION) / self.price_token0
+    }
+}
+
+/// Clear a batch at one uniform price, routing the net imbalance through a pool
+///
+/// `quote(sells_token0, amount)` is the pool's output for an exact input. The excess side
+/// sells just enough into the pool that the pool's average price matches the price at
+/// which the rest crosses peer-to-peer, so every included order gets the same rate. When
+/// the pool pays less than crossing directly, the sides cross at the ratio of their totals.
+/// Orders whose net payout (after `fee_bps`) misses their min_amount_out are excluded
+/// and the batch is re-cleared until every included order is satisfied.
+pub fn clear_batch(
+    orders: &[ClearingOrder],
+    fee_bps: U256,
+    quote: impl Fn(bool, U256) -> Option<U256>
+) -> Clearing {
+    let mut included: Vec<bool> = orders.iter().map(|order| order.eligible).collect();
+
+    loop {
+        let clearing = clear_once(orders, &included, &quote);
+
+        let mut changed = false;
+        for (i, order) in orders.iter().enumerate() {
+            if !clearing.included[i] {
+                continue;
+            }
+
+            let payout = clearing.payouts[i];
+            let net = payout - payout * fee_bps / U256::from(BPS_DENOMINATOR);
+            if payout == U256::ZERO || net < order.min_amount_out {
+                included[i] = false;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return clearing;
+        }
+    }
+}
+
+fn clear_once(
+    orders: &[ClearingOrder],
+    included: &[bool],
+    quote: &impl Fn(bool, U256) -> Option<U256>
+) -> Clearing {
+    let mut sum0 = U256::ZERO;
+    let mut sum1 = U256::ZERO;
+    for (order, _) in orders.iter().zip(included).filter(|(_, included)| **included) {
+        if order.sells_token0 {
+            sum0 += order.amount_in;
+        } else {
+            sum1 += order.amount_in;
+        }
+    }
+
+    let mut clearing = Clearing::unfilled(orders.len());
+    clearing.included = included.to_vec();
+
+    if sum0 == U256::ZERO && sum1 == U256::ZERO {
+        return clearing;
+    }
+
+    // Sell excess token0 into the pool, else excess token1, else cross the two sides directly
+    let (excess0, out0) = pool_leg(sum0, sum1, |amount| quote(true, amount));
+    if excess0 > U256::ZERO {
+        // sum0 token0 buy sum1 + out0 token1; token1 sellers split the token0 left after the pool leg
+        clearing.price_token0 = sum0;
+        clearing.price_token1 = sum1 + out0;
+        clearing.matched_token0 = sum0 - excess0;
+        clearing.matched_token1 = sum1;
+        clearing.pool_amount_in = excess0;
+        clearing.pool_sells_token0 = true;
+        clearing.pool_amount_out = out0;
+    } else {
+        let (excess1, out1) = pool_leg(sum1, sum0, |amount| quote(false, amount));
+        if excess1 > U256::ZERO {
+            clearing.price_token0 = sum0 + out1;
+            clearing.price_token1 = sum1;
+            clearing.matched_token0 = sum0;
+            clearing.matched_token1 = sum1 - excess1;
+            clearing.pool_amount_in = excess1;
+            clearing.pool_sells_token0 = false;
+            clearing.pool_amount_out = out1;
+        } else {
+            clearing.price_token0 = sum0;
+            clearing.price_token1 = sum1;
+            clearing.matched_token0 = sum0;
+            clearing.matched_token1 = sum1;
+        }
+    }
+
+    // Every order at the same price, rounded down so the router never pays out more than it holds
+    for (i, order) in orders.iter().enumerate() {
+        if !included[i] || clearing.price_token0 == U256::ZERO || clearing.price_token1 == U256::ZERO {
+            continue;
+        }
+
+        clearing.payouts[i] = if order.sells_token0 {
+            order.amount_in * clearing.price_token1 / clearing.price_token0
+        } else {
+            order.amount_in * clearing.price_token0 / clearing.price_token1
+        };
+    }
+
+    clearing
+}
+
+/// Largest part `x` of `excess_sum` to sell into the pool while the pool's average price
+/// is no worse than crossing the rest against the other side: out(x) / x >= other_sum / (excess_sum - x)
+/// That inequality is exactly what keeps the other side's payouts within excess_sum - x.
+/// Returns (x, out(x)), (0, 0) when the pool pays less than the direct cross
+fn pool_leg(excess_sum: U256, other_sum: U256, quote: impl Fn(U256) -> Option<U256>) -> (U256, U256) {
+    let solvent = |x: U256| -> Option<U256> {
+        let out = quote(x)?;
+        let pool_value = out.checked_mul(excess_sum - x)?;
+        let cross_value = x.checked_mul(other_sum)?;
+        (pool_value >= cross_value).then_some(out)
+    };
+
+    if excess_sum == U256::ZERO {
+        return (U256::ZERO, U256::ZERO);
+    }
+
+    // The whole side only goes to the pool when there is nothing to cross against
+    if let Some(out) = solvent(excess_sum) {
+        return (excess_sum, out);
+    }
+
+    // Average pool price falls as x grows while the cross price rises, so bisect the crossing point;
+    // `low` always satisfies the inequality
+    let mut low = U256::ZERO;
+    let mut low_out = U256::ZERO;
+    let mut high = excess_sum;
+    for _ in 0..MAX_CLEARING_STEPS {
+        if high - low <= U256::from(1) {
+            break;
+        }
+
+        let mid = low + (high - low) / U256::from(2);
+        match solvent(mid) {
+            Some(out) => {
+                low = mid;
+                low_out = out;
+            },
+            None => high = mid,
+        }
+    }
+
+    (low, low_out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(amount_in: u64, min_out: u64, sells_token0: bool) -> ClearingOrder {
+        ClearingOrder {
+            amount_in: U256::from(amount_in),
+            min_amount_out: U256::from(min_out),
+            sells_token0,
+            eligible: true,
+        }
+    }
+
+    // Pool quote that returns 90% of mid-price value (fee + impact)
+    fn lossy_quote(sells_token0: bool, amount: U256) -> Option<U256> {
+        let mid = if sells_token0 { amount * U256::from(2) } else { amount / U256::from(2) };
+        Some(mid * U256::from(9) / U256::from(10))
+    }
+
+    // Constant-product pool at 1 token0 = 2 token1 with a 0.3% fee
+    fn pool_quote(sells_token0: bool, amount: U256) -> Option<U256> {
+        let (reserve_in, reserve_out) = if sells_token0 {
+            (U256::from(1_000_000u64), U256::from(2_000_000u64))
+        } else {
+            (U256::from(2_000_000u64), U256::from(1_000_000u64))
+        };
+        let amount_with_fee = amount * U256::from(997);
+        Some(amount_with_fee * reserve_out / (reserve_in * U256::from(1000) + amount_with_fee))
+    }
+
+    /// token1 per token0 paid to an order, scaled by 1e6
+    fn rate(order: &ClearingOrder, payout: U256) -> U256 {
+        let scale = U256::from(1_000_000u64);
+        if order.sells_token0 {
+            payout * scale / order.amount_in
+        } else {
+            order.amount_in * scale / payout
+        }
+    }
+
+    #[test]
+    fn test_fully_matched_batch_skips_pool() {
+        // The sides cross at 2 token1 per token0, inside the pool's bid/ask
+        let orders = [order(100, 0, true), order(200, 0, false)];
+        let clearing = clear_batch(&orders, U256::ZERO, lossy_quote);
+
+        assert_eq!(clearing.pool_amount_in, U256::ZERO);
+        assert_eq!(clearing.payouts, vec![U256::from(200), U256::from(100)]);
+        assert_eq!(clearing.price(), U256::from(2) * U256::from(PRICE_PRECISION));
+    }
+
+    #[test]
+    fn test_net_imbalance_routed_through_pool() {
+        // 150 token0 vs 200 token1: the pool pays 1.8 per token0, so selling x into it
+        // is worth it while 1.8 >= 200 / (150 - x), i.e. x <= 38
+        let orders = [order(100, 0, true), order(50, 0, true), order(200, 0, false)];
+        let clearing = clear_batch(&orders, U256::ZERO, lossy_quote);
+
+        assert_eq!(clearing.pool_amount_in, U256::from(38));
+        assert_eq!(clearing.pool_amount_out, U256::from(68));
+        assert_eq!(clearing.matched_token0, U256::from(112));
+
+        // One price for everyone: 268 token1 per 150 token0
+        assert_eq!((clearing.price_token0, clearing.price_token1), (U256::from(150), U256::from(268)));
+        assert_eq!(clearing.payouts[0], U256::from(178));
+        assert_eq!(clearing.payouts[1], U256::from(89));
+        assert_eq!(clearing.payouts[2], U256::from(111));
+
+        // Token0 left after the pool leg covers the token1 seller
+        assert!(clearing.payouts[2] <= clearing.matched_token0);
+    }
+
+    #[test]
+    fn test_single_clearing_price_for_all_orders() {
+        for (orders, pool_sells_token0) in [
+            ([order(40_000, 0, true), order(10_000, 0, true), order(30_000, 0, false), order(5_000, 0, false)], true),
+            ([order(1_000, 0, true), order(4_000, 0, true), order(90_000, 0, false), order(60_000, 0, false)], false),
+        ] {
+            let clearing = clear_batch(&orders, U256::ZERO, pool_quote);
+            assert!(clearing.pool_amount_in > U256::ZERO);
+            assert_eq!(clearing.pool_sells_token0, pool_sells_token0);
+
+            // Every order trades within rounding of the same rate
+            let rates: Vec<U256> = orders.iter().zip(&clearing.payouts).map(|(order, payout)| rate(order, *payout)).collect();
+            let (min, max) = (*rates.iter().min().unwrap(), *rates.iter().max().unwrap());
+            assert!(max - min <= max / U256::from(1000), "rates diverge: {:?}", rates);
+
+            // Payouts never exceed what the batch and the pool leg provide
+            let (mut paid0, mut paid1, mut sold0, mut sold1) = (U256::ZERO, U256::ZERO, U256::ZERO, U256::ZERO);
+            for (order, payout) in orders.iter().zip(&clearing.payouts) {
+                if order.sells_token0 {
+                    sold0 += order.amount_in;
+                    paid1 += *payout;
+                } else {
+                    sold1 += order.amount_in;
+                    paid0 += *payout;
+                }
+            }
+            if clearing.pool_sells_token0 {
+                assert!(paid0 <= sold0 - clearing.pool_amount_in);
+                assert!(paid1 <= sold1 + clearing.pool_amount_out);
+            } else {
+                assert!(paid1 <= sold1 - clearing.pool_amount_in);
+                assert!(paid0 <= sold0 + clearing.pool_amount_out);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unsatisfied_orders_excluded() {
+        // Second order demands more than the clearing price can deliver
+        let orders = [order(100, 0, true), order(50, 200, true), order(200, 0, false)];
+        let clearing = clear_batch(&orders, U256::ZERO, lossy_quote);
+
+        assert_eq!(clearing.included, vec![true, false, true]);
+        assert_eq!(clearing.payouts[1], U256::ZERO);
+        assert_eq!(clearing.payouts[0], U256::from(200));
+        assert_eq!(clearing.pool_amount_in, U256::ZERO);
+    }
+
+    #[test]
+    fn test_ineligible_orders_and_unroutable_pool() {
+        let mut orders = [order(100, 0, true), order(200, 0, false)];
+        orders[1].eligible = false;
+
+        // Nothing to cross against: the whole side goes to the pool
+        let clearing = clear_batch(&orders, U256::ZERO, lossy_quote);
+        assert_eq!(clearing.included, vec![true, false]);
+        assert_eq!(clearing.pool_amount_in, U256::from(100));
+        assert_eq!(clearing.payouts[0], U256::from(180));
+
+        let clearing = clear_batch(&orders, U256::ZERO, |_, _| None);
+        assert_eq!(clearing.included, vec![false, false]);
+    }
+
+    #[test]
+    fn test_is_refundable() {
+        let end = U256::from(1000);
+        let deadline = end + U256::from(600);
+        let grace_end = end + U256::from(BATCH_SETTLEMENT_GRACE);
+
+        // Settlement is reserved to authorized callers while the order is live and within grace
+        assert!(!is_refundable(end, end, deadline));
+        assert!(!is_refundable(deadline, end, deadline));
+        // Past the order's deadline
+        assert!(is_refundable(deadline + U256::from(1), end, deadline));
+
+        // A long-lived order is still reclaimable once the batch sits unsettled past the grace period
+        let far_deadline = end + U256::from(10 * BATCH_SETTLEMENT_GRACE);
+        assert!(!is_refundable(grace_end - U256::from(1), end, far_deadline));
+        assert!(is_refundable(grace_end, end, far_deadline));
+    }
+}
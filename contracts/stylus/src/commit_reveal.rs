@@ -0,0 +1,69 @@
+// AquaFlow Commit-Reveal - Hide intent terms from sandwichers until execution
+// Users commit keccak256(intent_struct_hash ‖ salt) and reveal after a minimum block delay
+
+use stylus_sdk::{
+    alloy_primitives::{keccak256, Address, B256, U256},
+    prelude::*,
+};
+
+/// Stored commitment awaiting reveal
+#[derive(SolidityType, Clone)]
+pub struct IntentCommitment {
+    pub user: Address,         // Committer; the revealed intent must belong to them
+    pub commit_block: U256,    // Block the commitment was made in
+    pub is_pending: bool,      // False once revealed or cancelled
+}
+
+/// Commitment over an intent's EIP-712 struct hash and a secret salt
+pub fn commitment_hash(intent_struct_hash: B256, salt: B256) -> B256 {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(intent_struct_hash.as_slice());
+    data[32..].copy_from_slice(salt.as_slice());
+    keccak256(data)
+}
+
+/// Check that `current_block` is inside the reveal window of a commitment
+/// Reveals are allowed from commit_block + delay through commit_block + delay + window
+pub fn check_reveal_window(
+    commit_block: U256,
+    current_block: U256,
+    delay_blocks: U256,
+    window_blocks: U256
+) -> Result<(), &'static str> {
+    let opens_at = commit_block + delay_blocks;
+    if current_block < opens_at {
+        return Err("Reveal too early");
+    }
+
+    if current_block > opens_at + window_blocks {
+        return Err("Reveal window closed");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commitment_depends_on_salt() {
+        let intent_hash = B256::from([1u8; 32]);
+        let a = commitment_hash(intent_hash, B256::from([2u8; 32]));
+        let b = commitment_hash(intent_hash, B256::from([3u8; 32]));
+        assert_ne!(a, b);
+        assert_eq!(a, commitment_hash(intent_hash, B256::from([2u8; 32])));
+    }
+
+    #[test]
+    fn test_reveal_window() {
+        let commit = U256::from(100);
+        let delay = U256::from(2);
+        let window = U256::from(10);
+
+        assert_eq!(check_reveal_window(commit, U256::from(101), delay, window), Err("Reveal too early"));
+        assert!(check_reveal_window(commit, U256::from(102), delay, window).is_ok());
+        assert!(check_reveal_window(commit, U256::from(112), delay, window).is_ok());
+        assert_eq!(check_reveal_window(commit, U256::from(113), delay, window), Err("Reveal window closed"));
+    }
+}
@@ -0,0 +1,527 @@
+// AquaFlow Curve Adapter - StableSwap pools with any number of coins
+// Balances are normalized to 18 decimals and quoted with the StableSwap invariant
+
+use stylus_sdk::{
+    alloy_primitives::{Address, U256},
+    prelude::*,
+    call::Call,
+};
+
+use crate::pool_adapter::{self, PoolAdapter, PoolSnapshot, PoolView, SwapCall, SwapParams, SwapPayment, PRICE_PRECISION};
+use crate::tokens;
+
+/// PoolInfo.pool_type for Curve StableSwap pools
+pub const POOL_TYPE: u8 = 2;
+
+/// Largest pool the registry will read (coins beyond this are ignored as unsupported)
+pub const MAX_COINS: usize = 8;
+
+const BPS_DENOMINATOR: u64 = 10000;
+const MAX_ITERATIONS: usize = 255;
+const NORMALIZED_DECIMALS: u8 = 18;
+const STATE_HEADER_WORDS: usize = 6;
+
+sol_interface! {
+    interface ICurvePool {
+        function A() external view returns (uint256);
+        function coins(uint256 i) external view returns (address);
+        function balances(uint256 i) external view returns (uint256);
+    }
+}
+
+/// StableSwap parameters of the registry's token_a/token_b pair within a Curve pool
+/// Normalized balances of every coin are stored separately
+#[derive(SolidityType, Clone, Debug, PartialEq, Eq)]
+pub struct CurvePoolState {
+    pub amp: U256,     // Amplification coefficient A
+    pub n_coins: u8,
+    pub index_a: u8,   // Coin index of token_a
+    pub index_b: u8,   // Coin index of token_b
+    pub rate_a: U256,  // 10^(18 - decimals) for token_a
+    pub rate_b: U256,  // 10^(18 - decimals) for token_b
+}
+
+/// Pool snapshot read from chain
+pub struct CurveSnapshot {
+    pub state: CurvePoolState,
+    pub balances: Vec<U256>, // Normalized to 18 decimals
+    pub reserve_a: U256,     // Raw token_a balance
+    pub reserve_b: U256,     // Raw token_b balance
+}
+
+/// Multiplier bringing a `decimals` token amount to 18 decimals
+pub fn precision_multiplier(decimals: u8) -> Result<U256, &'static str> {
+    if decimals > NORMALIZED_DECIMALS {
+        return Err("Unsupported decimals");
+    }
+    Ok(U256::from(10).pow(U256::from(NORMALIZED_DECIMALS - decimals)))
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b { a - b } else { b - a }
+}
+
+/// StableSwap invariant D by Newton iteration (Curve's get_D)
+pub fn get_d(xp: &[U256], amp: U256) -> Result<U256, &'static str> {
+    let n = U256::from(xp.len());
+    let sum: U256 = xp.iter().fold(U256::ZERO, |acc, x| acc + *x);
+    if sum == U256::ZERO {
+        return Ok(U256::ZERO);
+    }
+
+    if xp.iter().any(|x| *x == U256::ZERO) {
+        return Err("Empty coin balance");
+    }
+
+    if amp == U256::ZERO {
+        return Err("Invalid amplification");
+    }
+
+    let ann = amp * n;
+    let mut d = sum;
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_p = d;
+        for x in xp {
+            d_p = d_p * d / (*x * n);
+        }
+
+        let d_prev = d;
+        let numerator = (ann * sum + d_p * n) * d;
+        let denominator = (ann - U256::from(1)) * d + (n + U256::from(1)) * d_p;
+        d = numerator / denominator;
+
+        if abs_diff(d, d_prev) <= U256::from(1) {
+            return Ok(d);
+        }
+    }
+
+    Err("StableSwap did not converge")
+}
+
+/// New balance of coin `j` when coin `i` is set to `x`, keeping D constant (Curve's get_y)
+pub fn get_y(i: usize, j: usize, x: U256, xp: &[U256], amp: U256) -> Result<U256, &'static str> {
+    if i == j || i >= xp.len() || j >= xp.len() {
+        return Err("Invalid coin index");
+    }
+
+    // The other balances are checked by get_d; a drained coin has no solution
+    if x == U256::ZERO {
+        return Err("Insufficient liquidity");
+    }
+
+    let n = U256::from(xp.len());
+    let d = get_d(xp, amp)?;
+    let ann = amp * n;
+
+    let mut c = d;
+    let mut sum = U256::ZERO;
+    for (k, balance) in xp.iter().enumerate() {
+        let value = if k == i {
+            x
+        } else if k != j {
+            *balance
+        } else {
+            continue;
+        };
+
+        sum += value;
+        c = c * d / (value * n);
+    }
+
+    c = c * d / (ann * n);
+    let b = sum + d / ann;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        y = (y * y + c) / (U256::from(2) * y + b - d);
+
+        if abs_diff(y, y_prev) <= U256::from(1) {
+            return Ok(y);
+        }
+    }
+
+    Err("StableSwap did not converge")
+}
+
+/// Exact-input quote from coin `i` to coin `j`
+/// `xp` holds normalized balances; rates convert raw amounts to and from 18 decimals
+pub fn quote_exact_in(
+    xp: &[U256],
+    amp: U256,
+    i: usize,
+    j: usize,
+    amount_in: U256,
+    rate_i: U256,
+    rate_j: U256,
+    fee_bps: U256
+) -> Result<U256, &'static str> {
+    if fee_bps >= U256::from(BPS_DENOMINATOR) {
+        return Err("Invalid fee");
+    }
+
+    if i >= xp.len() || j >= xp.len() {
+        return Err("Invalid coin index");
+    }
+
+    let x = xp[i] + amount_in * rate_i;
+    let y = get_y(i, j, x, xp, amp)?;
+
+    // Curve subtracts 1 wei to round against the trader
+    let dy_normalized = xp[j].checked_sub(y + U256::from(1)).ok_or("Insufficient liquidity")?;
+    let dy = dy_normalized / rate_j;
+    let fee = dy * fee_bps / U256::from(BPS_DENOMINATOR);
+    Ok(dy - fee)
+}
+
+/// Exact-output quote from coin `i` to coin `j`: input needed for `amount_out` after fees
+pub fn quote_exact_out(
+    xp: &[U256],
+    amp: U256,
+    i: usize,
+    j: usize,
+    amount_out: U256,
+    rate_i: U256,
+    rate_j: U256,
+    fee_bps: U256
+) -> Result<U256, &'static str> {
+    if fee_bps >= U256::from(BPS_DENOMINATOR) {
+        return Err("Invalid fee");
+    }
+
+    if i >= xp.len() || j >= xp.len() {
+        return Err("Invalid coin index");
+    }
+
+    // Gross output before the fee, rounded up
+    let fee_factor = U256::from(BPS_DENOMINATOR) - fee_bps;
+    let dy = (amount_out * U256::from(BPS_DENOMINATOR) + fee_factor - U256::from(1)) / fee_factor;
+
+    // Mirror quote_exact_in's 1 wei rounding, then solve for coin i's new balance
+    let y = xp[j].checked_sub(dy * rate_j + U256::from(1)).ok_or("Insufficient liquidity")?;
+    let x = get_y(j, i, y, xp, amp)?;
+
+    let dx_normalized = x.checked_sub(xp[i]).ok_or("Insufficient liquidity")?;
+    Ok(dx_normalized / rate_i + U256::from(1))
+}
+
+/// Marginal price of coin `i` in coin `j` before fees, in PRICE_PRECISION units of raw amounts
+pub fn spot_price(xp: &[U256], amp: U256, i: usize, j: usize, rate_i: U256, rate_j: U256) -> Result<U256, &'static str> {
+    if i == j || i >= xp.len() || j >= xp.len() {
+        return Err("Invalid coin index");
+    }
+
+    let n = U256::from(xp.len());
+    let d = get_d(xp, amp)?;
+    if d == U256::ZERO {
+        return Err("Insufficient liquidity");
+    }
+
+    let mut d_p = d;
+    for x in xp {
+        d_p = d_p * d / (*x * n);
+    }
+
+    // -dx_j/dx_i of the invariant: (ann * x_i + D_P) * x_j / ((ann * x_j + D_P) * x_i)
+    let ann = amp * n;
+    let numerator = (ann * xp[i] + d_p) * xp[j] / xp[i];
+    let denominator = ann * xp[j] + d_p;
+    Ok(numerator * U256::from(PRICE_PRECISION) / denominator * rate_i / rate_j)
+}
+
+/// Registry state words of a Curve pool: CurvePoolState fields, then every normalized balance
+pub fn encode_state(state: &CurvePoolState, balances: &[U256]) -> Vec<U256> {
+    let mut words = vec![
+        state.amp,
+        U256::from(state.n_coins),
+        U256::from(state.index_a),
+        U256::from(state.index_b),
+        state.rate_a,
+        state.rate_b,
+    ];
+    words.extend_from_slice(balances);
+    words
+}
+
+/// CurvePoolState and normalized balances stored by encode_state
+pub fn decode_state(words: &[U256]) -> Result<(CurvePoolState, Vec<U256>), &'static str> {
+    if words.len() < STATE_HEADER_WORDS {
+        return Err("Invalid pool state");
+    }
+
+    let n_coins = words.len() - STATE_HEADER_WORDS;
+    let n = U256::from(n_coins);
+    if n_coins > MAX_COINS || words[1] != n || words[2] >= n || words[3] >= n {
+        return Err("Invalid pool state");
+    }
+
+    let state = CurvePoolState {
+        amp: words[0],
+        n_coins: n_coins as u8,
+        index_a: words[2].to::<u8>(),
+        index_b: words[3].to::<u8>(),
+        rate_a: words[4],
+        rate_b: words[5],
+    };
+    let balances = words[STATE_HEADER_WORDS..].to_vec();
+
+    Ok((state, balances))
+}
+
+/// Read coins, balances, decimals and A for a Curve pool
+/// Reverting or non-conforming contracts are reported as errors
+pub fn fetch_state(pool_address: Address, token_a: Address, token_b: Address) -> Result<CurveSnapshot, Vec<u8>> {
+    let pool = ICurvePool::new(pool_address);
+
+    let amp = pool.a(Call::new())
+        .map_err(|_| b"Pool amplification unavailable".to_vec())?;
+
+    let mut balances = Vec::new();
+    let mut index_a = None;
+    let mut index_b = None;
+    let mut rates = Vec::new();
+    let mut raw = Vec::new();
+
+    // coins(i) reverts past the last coin
+    for i in 0..MAX_COINS {
+        let coin = match pool.coins(Call::new(), U256::from(i)) {
+            Ok(coin) => coin,
+            Err(_) => break,
+        };
+
+        let balance = pool.balances(Call::new(), U256::from(i))
+            .map_err(|_| b"Pool balances unavailable".to_vec())?;
+        let rate = precision_multiplier(tokens::token_decimals(coin)?)
+            .map_err(|e| e.as_bytes().to_vec())?;
+
+        if coin == token_a {
+            index_a = Some(i);
+        } else if coin == token_b {
+            index_b = Some(i);
+        }
+
+        balances.push(balance * rate);
+        rates.push(rate);
+        raw.push(balance);
+    }
+
+    let (index_a, index_b) = match (index_a, index_b) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return Err(b"Pool tokens mismatch".to_vec()),
+    };
+
+    Ok(CurveSnapshot {
+        state: CurvePoolState {
+            amp,
+            n_coins: balances.len() as u8,
+            index_a: index_a as u8,
+            index_b: index_b as u8,
+            rate_a: rates[index_a],
+            rate_b: rates[index_b],
+        },
+        balances,
+        reserve_a: raw[index_a],
+        reserve_b: raw[index_b],
+    })
+}
+
+/// Adapter for Curve StableSwap pools
+pub struct CurveAdapter;
+
+impl CurveAdapter {
+    /// Decoded state with (i, j, rate_i, rate_j) for the swap direction
+    fn direction(pool: &PoolView, a_to_b: bool) -> Result<(CurvePoolState, Vec<U256>, usize, usize, U256, U256), &'static str> {
+        let (state, xp) = decode_state(pool.state)?;
+        let (i, j, rate_i, rate_j) = if a_to_b {
+            (state.index_a, state.index_b, state.rate_a, state.rate_b)
+        } else {
+            (state.index_b, state.index_a, state.rate_b, state.rate_a)
+        };
+        Ok((state, xp, i as usize, j as usize, rate_i, rate_j))
+    }
+}
+
+impl PoolAdapter for CurveAdapter {
+    fn pool_type(&self) -> u8 {
+        POOL_TYPE
+    }
+
+    fn fetch_state(&self, pool_address: Address, token_a: Address, token_b: Address) -> Result<PoolSnapshot, Vec<u8>> {
+        let snapshot = fetch_state(pool_address, token_a, token_b)?;
+        Ok(PoolSnapshot {
+            reserve_a: snapshot.reserve_a,
+            reserve_b: snapshot.reserve_b,
+            state: encode_state(&snapshot.state, &snapshot.balances),
+        })
+    }
+
+    fn quote_exact_in(&self, pool: &PoolView, a_to_b: bool, amount_in: U256) -> Result<U256, &'static str> {
+        let (state, xp, i, j, rate_i, rate_j) = Self::direction(pool, a_to_b)?;
+        quote_exact_in(&xp, state.amp, i, j, amount_in, rate_i, rate_j, pool.fee_bps)
+    }
+
+    fn quote_exact_out(&self, pool: &PoolView, a_to_b: bool, amount_out: U256) -> Result<U256, &'static str> {
+        let (state, xp, i, j, rate_i, rate_j) = Self::direction(pool, a_to_b)?;
+        quote_exact_out(&xp, state.amp, i, j, amount_out, rate_i, rate_j, pool.fee_bps)
+    }
+
+    fn spot_price(&self, pool: &PoolView, a_to_b: bool) -> Result<U256, &'static str> {
+        let (state, xp, i, j, rate_i, rate_j) = Self::direction(pool, a_to_b)?;
+        spot_price(&xp, state.amp, i, j, rate_i, rate_j)
+    }
+
+    /// Every coin of the pool, not just the registered pair
+    fn holdings(&self, pool: &PoolView) -> Result<Vec<(Address, U256)>, Vec<u8>> {
+        let (state, _) = decode_state(pool.state).map_err(|e| e.as_bytes().to_vec())?;
+        let curve_pool = ICurvePool::new(pool.pool_address);
+
+        (0..state.n_coins)
+            .map(|i| {
+                let coin = curve_pool.coins(Call::new(), U256::from(i))
+                    .map_err(|_| b"Pool coins unavailable".to_vec())?;
+                let balance = curve_pool.balances(Call::new(), U256::from(i))
+                    .map_err(|_| b"Pool balances unavailable".to_vec())?;
+                Ok((coin, balance))
+            })
+            .collect()
+    }
+
+    fn payment(&self) -> SwapPayment {
+        SwapPayment::Allowance
+    }
+
+    /// pool.exchange(i, j, dx, min_dy); the pool pulls token_in from and pays token_out to the caller
+    fn encode_swap(&self, pool: &PoolView, swap: &SwapParams) -> Result<SwapCall, &'static str> {
+        let (_, _, i, j, _, _) = Self::direction(pool, swap.a_to_b)?;
+        Ok(SwapCall {
+            target: pool.pool_address,
+            calldata: pool_adapter::encode_call("exchange(int128,int128,uint256,uint256)", &[
+                U256::from(i),
+                U256::from(j),
+                swap.amount_in,
+                swap.min_amount_out,
+            ]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn e18(amount: u64) -> U256 {
+        U256::from(amount) * U256::from(10).pow(U256::from(18))
+    }
+
+    #[test]
+    fn test_balanced_pool_invariant_is_sum() {
+        let xp = [e18(1_000_000), e18(1_000_000)];
+        let d = get_d(&xp, U256::from(100)).unwrap();
+        assert!(abs_diff(d, e18(2_000_000)) <= U256::from(1));
+    }
+
+    #[test]
+    fn test_stable_quote_near_one_to_one() {
+        // 1M/1M pool, A = 100, swap 1000 with no fee
+        let xp = [e18(1_000_000), e18(1_000_000)];
+        let one = U256::from(1);
+        let out = quote_exact_in(&xp, U256::from(100), 0, 1, e18(1000), one, one, U256::ZERO).unwrap();
+
+        // Constant product loses ~1 token here; A = 100 cuts that about a hundredfold
+        let constant_product = e18(1000) * e18(1_000_000) / (e18(1_000_000) + e18(1000));
+        assert!(out > constant_product);
+        assert!(out <= e18(1000));
+        assert!(e18(1000) - out < e18(1) / U256::from(50));
+    }
+
+    #[test]
+    fn test_three_coin_pool_with_mixed_decimals() {
+        // DAI (18), USDC (6), USDT (6), balanced at 1M each
+        let usdc_rate = precision_multiplier(6).unwrap();
+        let xp = [e18(1_000_000), e18(1_000_000), e18(1_000_000)];
+
+        // 1000 DAI -> USDC: about 1000 * 10^6 raw units less 4 bps fee
+        let out = quote_exact_in(&xp, U256::from(2000), 0, 1, e18(1000), U256::from(1), usdc_rate, U256::from(4)).unwrap();
+        let expected = U256::from(1_000_000_000u64) * U256::from(9996) / U256::from(10000);
+        assert!(abs_diff(out, expected) < U256::from(1000));
+    }
+
+    #[test]
+    fn test_imbalanced_pool_pays_less() {
+        let one = U256::from(1);
+        let balanced = [e18(1_000_000), e18(1_000_000)];
+        let drained = [e18(1_900_000), e18(100_000)];
+
+        let fair = quote_exact_in(&balanced, U256::from(100), 0, 1, e18(1000), one, one, U256::ZERO).unwrap();
+        let skewed = quote_exact_in(&drained, U256::from(100), 0, 1, e18(1000), one, one, U256::ZERO).unwrap();
+        assert!(skewed < fair);
+    }
+
+    #[test]
+    fn test_exact_out_inverts_quote() {
+        let usdc_rate = precision_multiplier(6).unwrap();
+        let xp = [e18(1_000_000), e18(1_200_000), e18(900_000)];
+        let (amp, fee) = (U256::from(200), U256::from(4));
+
+        // DAI needed for 5000 USDC
+        let amount_out = U256::from(5_000_000_000u64);
+        let amount_in = quote_exact_out(&xp, amp, 0, 1, amount_out, U256::from(1), usdc_rate, fee).unwrap();
+        assert!(quote_exact_in(&xp, amp, 0, 1, amount_in, U256::from(1), usdc_rate, fee).unwrap() >= amount_out);
+
+        // Tight to within one raw USDC unit (1e12 normalized)
+        let less = amount_in - U256::from(2_000_000_000_000u64);
+        assert!(quote_exact_in(&xp, amp, 0, 1, less, U256::from(1), usdc_rate, fee).unwrap() < amount_out);
+    }
+
+    #[test]
+    fn test_exact_out_draining_coin_is_rejected() {
+        // Asking for all of coin 1 but the 1 wei rounding buffer leaves it at exactly zero
+        let xp = [e18(1000), e18(1000) + U256::from(1)];
+        let one = U256::from(1);
+        let result = quote_exact_out(&xp, U256::from(100), 0, 1, e18(1000), one, one, U256::ZERO);
+        assert_eq!(result, Err("Insufficient liquidity"));
+
+        assert_eq!(get_y(0, 1, U256::ZERO, &xp, U256::from(100)), Err("Insufficient liquidity"));
+    }
+
+    #[test]
+    fn test_spot_price() {
+        let one = U256::from(1);
+        let balanced = [e18(1_000_000), e18(1_000_000)];
+        let price = spot_price(&balanced, U256::from(100), 0, 1, one, one).unwrap();
+        assert!(abs_diff(price, U256::from(PRICE_PRECISION)) <= U256::from(1));
+
+        // Matches the output of a tiny swap in a skewed pool
+        let skewed = [e18(1_500_000), e18(500_000)];
+        let price = spot_price(&skewed, U256::from(100), 0, 1, one, one).unwrap();
+        let tiny = quote_exact_in(&skewed, U256::from(100), 0, 1, e18(1), one, one, U256::ZERO).unwrap();
+        assert!(price < U256::from(PRICE_PRECISION));
+        assert!(abs_diff(price, tiny) < U256::from(PRICE_PRECISION / 1_000_000));
+    }
+
+    #[test]
+    fn test_state_words_round_trip() {
+        let state = CurvePoolState {
+            amp: U256::from(2000),
+            n_coins: 3,
+            index_a: 2,
+            index_b: 0,
+            rate_a: precision_multiplier(6).unwrap(),
+            rate_b: U256::from(1),
+        };
+        let balances = vec![e18(1), e18(2), e18(3)];
+
+        let words = encode_state(&state, &balances);
+        assert_eq!(decode_state(&words), Ok((state, balances)));
+        assert_eq!(decode_state(&words[..8]), Err("Invalid pool state"));
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        assert_eq!(precision_multiplier(24), Err("Unsupported decimals"));
+        let xp = [e18(1), e18(1)];
+        assert_eq!(get_y(0, 0, e18(1), &xp, U256::from(100)), Err("Invalid coin index"));
+        assert_eq!(get_d(&[e18(1), U256::ZERO], U256::from(100)), Err("Empty coin balance"));
+    }
+}
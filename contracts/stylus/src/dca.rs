@@ -0,0 +1,115 @@
+// AquaFlow DCA Schedules - Recurring intents funded from an escrowed budget
+// Each due period is executed as a regular ValidatedIntent through the security pipeline
+
+use stylus_sdk::{
+    alloy_primitives::{Address, U256},
+    prelude::*,
+};
+
+/// Minimum time between two executions of the same schedule
+pub const MIN_DCA_INTERVAL: u64 = 3600; // 1 hour
+
+/// Recurring swap schedule with escrowed token_in budget
+#[derive(SolidityType, Clone)]
+pub struct DcaSchedule {
+    pub user: Address,
+    pub token_in: Address,
+    pub token_out: Address,
+    pub amount_per_period: U256,         // token_in sold each period
+    pub min_amount_out_per_period: U256, // Optional price floor per period (0 = none)
+    pub interval: U256,                  // Seconds between executions
+    pub max_slippage_bps: U256,
+    pub budget_remaining: U256,          // Escrowed token_in not yet spent
+    pub next_execution: U256,            // Timestamp the next period becomes due
+    pub executions: U256,                // Periods executed so far
+    pub is_active: bool,
+}
+
+impl DcaSchedule {
+    /// Check whether a period can be executed at `now`
+    pub fn is_due(&self, now: U256) -> bool {
+        self.is_active && now >= self.next_execution
+    }
+
+    /// Amount sold this period
+    /// A remainder that would leave less than `min_trade_amount` for a later period is folded into this one,
+    /// so the schedule never ends on a period too small to execute
+    pub fn period_amount(&self, min_trade_amount: U256) -> U256 {
+        if self.budget_remaining < self.amount_per_period + min_trade_amount {
+            return self.budget_remaining;
+        }
+        self.amount_per_period
+    }
+}
+
+/// Next due timestamp after an execution at `now`
+/// Missed periods are skipped rather than executed in a burst
+pub fn next_execution_time(scheduled: U256, interval: U256, now: U256) -> U256 {
+    let next = scheduled + interval;
+    if next <= now {
+        now + interval
+    } else {
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(budget: u64, per_period: u64) -> DcaSchedule {
+        DcaSchedule {
+            user: Address::from([1u8; 20]),
+            token_in: Address::from([2u8; 20]),
+            token_out: Address::from([3u8; 20]),
+            amount_per_period: U256::from(per_period),
+            min_amount_out_per_period: U256::ZERO,
+            interval: U256::from(MIN_DCA_INTERVAL),
+            max_slippage_bps: U256::from(100),
+            budget_remaining: U256::from(budget),
+            next_execution: U256::from(1000),
+            executions: U256::ZERO,
+            is_active: true,
+        }
+    }
+
+    #[test]
+    fn test_is_due() {
+        let mut s = schedule(1000, 100);
+        assert!(!s.is_due(U256::from(999)));
+        assert!(s.is_due(U256::from(1000)));
+
+        s.is_active = false;
+        assert!(!s.is_due(U256::from(5000)));
+    }
+
+    #[test]
+    fn test_period_amount_capped_by_budget() {
+        let min_trade = U256::from(10);
+        assert_eq!(schedule(1000, 100).period_amount(min_trade), U256::from(100));
+        assert_eq!(schedule(50, 100).period_amount(min_trade), U256::from(50));
+    }
+
+    #[test]
+    fn test_period_amount_folds_dust_remainder() {
+        let min_trade = U256::from(10);
+
+        // 105 would leave 5 for a last period below the minimum: sell it all now
+        assert_eq!(schedule(105, 100).period_amount(min_trade), U256::from(105));
+
+        // 110 leaves exactly the minimum, which can still execute on its own
+        assert_eq!(schedule(110, 100).period_amount(min_trade), U256::from(100));
+        assert_eq!(schedule(10, 100).period_amount(min_trade), U256::from(10));
+    }
+
+    #[test]
+    fn test_next_execution_skips_missed_periods() {
+        let interval = U256::from(100);
+
+        // On time: advance by one interval
+        assert_eq!(next_execution_time(U256::from(1000), interval, U256::from(1010)), U256::from(1100));
+
+        // Several periods late: no catch-up burst
+        assert_eq!(next_execution_time(U256::from(1000), interval, U256::from(1500)), U256::from(1600));
+    }
+}
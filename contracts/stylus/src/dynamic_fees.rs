@@ -0,0 +1,127 @@
+// AquaFlow Dynamic Fees - Volatility-scaled pool fees
+// Volatility is an EWMA of per-block spot price changes observed before each swap
+
+use stylus_sdk::{
+    alloy_primitives::U256,
+    prelude::*,
+};
+
+const BPS_DENOMINATOR: u64 = 10000;
+const PRICE_PRECISION: u64 = 1_000_000_000_000_000_000; // 1e18
+
+/// Weight of the newest observation in the volatility EWMA (20%)
+pub const VOLATILITY_ALPHA_BPS: u64 = 2000;
+
+/// Per-pool dynamic fee settings
+#[derive(SolidityType, Clone)]
+pub struct DynamicFeeConfig {
+    pub enabled: bool,
+    pub sensitivity_bps: U256, // Fee bps added per 10000 bps of volatility
+    pub max_fee_bps: U256,     // Cap, never above the router's MAX_FEE_BPS
+}
+
+/// Per-pool volatility tracking
+#[derive(SolidityType, Clone)]
+pub struct VolatilityState {
+    pub last_price: U256,     // Spot price (token_b per token_a, 1e18) at last observation
+    pub last_block: U256,     // Block of last observation
+    pub volatility_bps: U256, // EWMA of per-block price change
+}
+
+/// Spot price of token_a in token_b, scaled by 1e18
+pub fn spot_price(reserve_a: U256, reserve_b: U256) -> U256 {
+    if reserve_a == U256::ZERO {
+        return U256::ZERO;
+    }
+    reserve_b * U256::from(PRICE_PRECISION) / reserve_a
+}
+
+/// Absolute price change in basis points
+pub fn price_change_bps(old_price: U256, new_price: U256) -> U256 {
+    if old_price == U256::ZERO {
+        return U256::ZERO;
+    }
+
+    let diff = if new_price > old_price {
+        new_price - old_price
+    } else {
+        old_price - new_price
+    };
+
+    diff * U256::from(BPS_DENOMINATOR) / old_price
+}
+
+/// Fold a new per-block price change into the volatility EWMA
+pub fn update_volatility(volatility_bps: U256, change_bps: U256) -> U256 {
+    let alpha = U256::from(VOLATILITY_ALPHA_BPS);
+    let denominator = U256::from(BPS_DENOMINATOR);
+    (volatility_bps * (denominator - alpha) + change_bps * alpha) / denominator
+}
+
+/// Effective fee: base fee plus a volatility premium, capped at `max_fee_bps`
+pub fn effective_fee_bps(
+    base_fee_bps: U256,
+    volatility_bps: U256,
+    sensitivity_bps: U256,
+    max_fee_bps: U256
+) -> U256 {
+    let premium = volatility_bps * sensitivity_bps / U256::from(BPS_DENOMINATOR);
+    core::cmp::min(base_fee_bps + premium, max_fee_bps)
+}
+
+/// Premium withheld from a pool's output when the effective fee exceeds the fee the pool itself charged
+/// `amount_out` is already net of the pool fee, so the premium is scaled to the post-fee amount
+pub fn fee_surcharge(amount_out: U256, pool_fee_bps: U256, effective_fee_bps: U256) -> U256 {
+    if effective_fee_bps <= pool_fee_bps || pool_fee_bps >= U256::from(BPS_DENOMINATOR) {
+        return U256::ZERO;
+    }
+
+    amount_out * (effective_fee_bps - pool_fee_bps) / (U256::from(BPS_DENOMINATOR) - pool_fee_bps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spot_price() {
+        let price = spot_price(U256::from(1000), U256::from(2000));
+        assert_eq!(price, U256::from(2) * U256::from(PRICE_PRECISION));
+        assert_eq!(spot_price(U256::ZERO, U256::from(2000)), U256::ZERO);
+    }
+
+    #[test]
+    fn test_price_change_bps() {
+        assert_eq!(price_change_bps(U256::from(1000), U256::from(1050)), U256::from(500));
+        assert_eq!(price_change_bps(U256::from(1000), U256::from(950)), U256::from(500));
+        assert_eq!(price_change_bps(U256::ZERO, U256::from(950)), U256::ZERO);
+    }
+
+    #[test]
+    fn test_update_volatility_ewma() {
+        // 80% of 100 + 20% of 600 = 200
+        assert_eq!(update_volatility(U256::from(100), U256::from(600)), U256::from(200));
+        // Calm blocks decay volatility
+        assert_eq!(update_volatility(U256::from(200), U256::ZERO), U256::from(160));
+    }
+
+    #[test]
+    fn test_effective_fee_bounded() {
+        // 30 + 500 * 2000 / 10000 = 130
+        let fee = effective_fee_bps(U256::from(30), U256::from(500), U256::from(2000), U256::from(1000));
+        assert_eq!(fee, U256::from(130));
+
+        let capped = effective_fee_bps(U256::from(30), U256::from(10000), U256::from(20000), U256::from(1000));
+        assert_eq!(capped, U256::from(1000));
+    }
+
+    #[test]
+    fn test_fee_surcharge_matches_effective_fee() {
+        // 1,000,000 in: pool at 30 bps pays 997,000; at 130 bps the user keeps 987,000
+        let surcharge = fee_surcharge(U256::from(997_000), U256::from(30), U256::from(130));
+        assert_eq!(U256::from(997_000) - surcharge, U256::from(987_000));
+
+        assert_eq!(fee_surcharge(U256::from(997_000), U256::from(30), U256::from(30)), U256::ZERO);
+        assert_eq!(fee_surcharge(U256::from(997_000), U256::from(30), U256::from(10)), U256::ZERO);
+    }
+}
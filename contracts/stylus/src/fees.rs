@@ -0,0 +1,59 @@
+// AquaFlow Fee Helpers - Protocol fee and referral split
+// Referral fees are carved out of the protocol fee, never charged on top of it
+
+use stylus_sdk::alloy_primitives::U256;
+
+const BPS_DENOMINATOR: u64 = 10000;
+
+/// Result of charging the protocol fee on a swap output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub net_amount: U256,   // Delivered to the recipient
+    pub protocol_fee: U256, // Retained by the protocol
+    pub referral_fee: U256, // Credited to the referrer
+}
+
+/// Charge the protocol fee on `amount_out` and credit the referrer's share
+/// The referral fee is capped at the protocol fee so referrals never cost the user extra
+pub fn split_protocol_fee(amount_out: U256, protocol_fee_bps: U256, referral_fee_bps: U256) -> FeeSplit {
+    let total_fee = amount_out * protocol_fee_bps / U256::from(BPS_DENOMINATOR);
+    let referral_fee = core::cmp::min(
+        amount_out * referral_fee_bps / U256::from(BPS_DENOMINATOR),
+        total_fee
+    );
+
+    FeeSplit {
+        net_amount: amount_out - total_fee,
+        protocol_fee: total_fee - referral_fee,
+        referral_fee,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_without_referral() {
+        let split = split_protocol_fee(U256::from(10000), U256::from(30), U256::ZERO);
+        assert_eq!(split.net_amount, U256::from(9970));
+        assert_eq!(split.protocol_fee, U256::from(30));
+        assert_eq!(split.referral_fee, U256::ZERO);
+    }
+
+    #[test]
+    fn test_referral_comes_out_of_protocol_fee() {
+        let split = split_protocol_fee(U256::from(10000), U256::from(30), U256::from(10));
+        assert_eq!(split.net_amount, U256::from(9970));
+        assert_eq!(split.protocol_fee, U256::from(20));
+        assert_eq!(split.referral_fee, U256::from(10));
+    }
+
+    #[test]
+    fn test_referral_capped_at_protocol_fee() {
+        let split = split_protocol_fee(U256::from(10000), U256::from(30), U256::from(50));
+        assert_eq!(split.net_amount, U256::from(9970));
+        assert_eq!(split.protocol_fee, U256::ZERO);
+        assert_eq!(split.referral_fee, U256::from(30));
+    }
+}
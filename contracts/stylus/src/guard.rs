@@ -0,0 +1,87 @@
+// AquaFlow Reentrancy Guard - Storage-backed lock shared by router and registry
+// A failed entry point reverts all storage writes, so only the success path must release the lock
+
+/// Contracts holding a storage-backed reentrancy flag
+pub trait NonReentrant {
+    fn reentrancy_locked(&self) -> bool;
+    fn set_reentrancy_locked(&mut self, locked: bool);
+
+    /// Acquire the lock at the start of a mutating entry point
+    fn enter_non_reentrant(&mut self) -> Result<(), Vec<u8>> {
+        if self.reentrancy_locked() {
+            return Err(b"Reentrant call".to_vec());
+        }
+
+        self.set_reentrancy_locked(true);
+        Ok(())
+    }
+
+    /// Release the lock before a mutating entry point returns successfully
+    fn exit_non_reentrant(&mut self) {
+        self.set_reentrancy_locked(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal contract whose payout calls into an untrusted token
+    struct MockRouter {
+        locked: bool,
+        balance: u64,
+        reentry_result: Option<Result<u64, Vec<u8>>>,
+    }
+
+    impl NonReentrant for MockRouter {
+        fn reentrancy_locked(&self) -> bool {
+            self.locked
+        }
+
+        fn set_reentrancy_locked(&mut self, locked: bool) {
+            self.locked = locked;
+        }
+    }
+
+    impl MockRouter {
+        fn new(balance: u64) -> Self {
+            Self { locked: false, balance, reentry_result: None }
+        }
+
+        /// Guarded withdrawal; `token` stands in for the external transfer call
+        fn withdraw(&mut self, token: &mut dyn FnMut(&mut MockRouter)) -> Result<u64, Vec<u8>> {
+            self.enter_non_reentrant()?;
+
+            let amount = self.balance;
+            token(self);
+            self.balance = 0;
+
+            self.exit_non_reentrant();
+            Ok(amount)
+        }
+    }
+
+    #[test]
+    fn test_malicious_token_cannot_reenter() {
+        let mut router = MockRouter::new(100);
+
+        // Token re-enters withdraw during the transfer callback
+        let mut malicious_token = |router: &mut MockRouter| {
+            let result = router.withdraw(&mut |_| {});
+            router.reentry_result = Some(result);
+        };
+
+        assert_eq!(router.withdraw(&mut malicious_token), Ok(100));
+        assert_eq!(router.reentry_result, Some(Err(b"Reentrant call".to_vec())));
+        assert_eq!(router.balance, 0);
+    }
+
+    #[test]
+    fn test_lock_released_after_success() {
+        let mut router = MockRouter::new(100);
+
+        assert_eq!(router.withdraw(&mut |_| {}), Ok(100));
+        assert!(!router.locked);
+        assert_eq!(router.withdraw(&mut |_| {}), Ok(0));
+    }
+}
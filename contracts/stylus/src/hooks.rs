@@ -0,0 +1,130 @@
+// AquaFlow Swap Hooks - Per-pool and per-user beforeSwap/afterSwap callbacks
+// Hooks run with a gas cap; only hooks granted HOOK_CAN_BLOCK can stop a swap
+
+use stylus_sdk::{
+    alloy_primitives::{Address, U256},
+    prelude::*,
+    call::Call,
+};
+
+/// Permission bits
+pub const HOOK_BEFORE_SWAP: u8 = 1 << 0; // Call beforeSwap ahead of each hop
+pub const HOOK_AFTER_SWAP: u8 = 1 << 1;  // Call afterSwap after each hop
+pub const HOOK_CAN_BLOCK: u8 = 1 << 2;   // A failed call or `false` return reverts the swap
+pub const HOOK_PERMISSION_MASK: u8 = HOOK_BEFORE_SWAP | HOOK_AFTER_SWAP | HOOK_CAN_BLOCK;
+
+/// Upper bound for the per-hook gas cap
+pub const MAX_HOOK_GAS: u64 = 500000;
+
+sol_interface! {
+    interface ISwapHook {
+        function beforeSwap(address user, uint256 pool_id, address token_in, address token_out, uint256 amount_in) external returns (bool);
+        function afterSwap(address user, uint256 pool_id, address token_in, address token_out, uint256 amount_in, uint256 amount_out) external returns (bool);
+    }
+}
+
+/// Hook attached to a pool or a user
+#[derive(SolidityType, Clone)]
+pub struct HookConfig {
+    pub hook: Address,     // Zero = no hook
+    pub permissions: u8,   // HOOK_* bitmap
+    pub gas_limit: U256,   // Gas forwarded to each callback
+}
+
+impl HookConfig {
+    pub fn is_set(&self) -> bool {
+        self.hook != Address::ZERO
+    }
+
+    pub fn has_permission(&self, flag: u8) -> bool {
+        self.permissions & flag == flag
+    }
+}
+
+/// Validate a hook configuration before storing it
+pub fn validate_hook_config(hook: Address, permissions: u8, gas_limit: U256) -> Result<(), &'static str> {
+    if hook == Address::ZERO {
+        return Ok(()); // Clearing a hook
+    }
+
+    if permissions & !HOOK_PERMISSION_MASK != 0 {
+        return Err("Unknown hook permission");
+    }
+
+    if permissions & (HOOK_BEFORE_SWAP | HOOK_AFTER_SWAP) == 0 {
+        return Err("Hook has no callbacks");
+    }
+
+    if gas_limit == U256::ZERO || gas_limit > U256::from(MAX_HOOK_GAS) {
+        return Err("Invalid hook gas limit");
+    }
+
+    Ok(())
+}
+
+/// Swap details passed to hook callbacks
+pub struct HookSwap {
+    pub user: Address,
+    pub pool_id: U256,
+    pub token_in: Address,
+    pub token_out: Address,
+    pub amount_in: U256,
+    pub amount_out: U256,
+}
+
+/// Invoke the `stage` callback (HOOK_BEFORE_SWAP or HOOK_AFTER_SWAP)
+/// Returns Ok(false) when a non-blocking hook fails, so the caller can report it
+pub fn invoke_hook(
+    storage: &mut impl TopLevelStorage,
+    config: &HookConfig,
+    stage: u8,
+    swap: &HookSwap
+) -> Result<bool, Vec<u8>> {
+    let hook = ISwapHook::new(config.hook);
+    let call = Call::new_in(storage).gas(config.gas_limit.to::<u64>());
+
+    let result = if stage == HOOK_BEFORE_SWAP {
+        hook.before_swap(call, swap.user, swap.pool_id, swap.token_in, swap.token_out, swap.amount_in)
+    } else {
+        hook.after_swap(call, swap.user, swap.pool_id, swap.token_in, swap.token_out, swap.amount_in, swap.amount_out)
+    };
+
+    let approved = matches!(result, Ok(true));
+    if !approved && config.has_permission(HOOK_CAN_BLOCK) {
+        return Err(b"Swap blocked by hook".to_vec());
+    }
+
+    Ok(approved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_hook_config() {
+        let hook = Address::from([7u8; 20]);
+        let gas = U256::from(100000);
+
+        assert!(validate_hook_config(Address::ZERO, 0, U256::ZERO).is_ok());
+        assert!(validate_hook_config(hook, HOOK_BEFORE_SWAP | HOOK_CAN_BLOCK, gas).is_ok());
+        assert!(validate_hook_config(hook, HOOK_CAN_BLOCK, gas).is_err());
+        assert!(validate_hook_config(hook, 1 << 5, gas).is_err());
+        assert!(validate_hook_config(hook, HOOK_AFTER_SWAP, U256::ZERO).is_err());
+        assert!(validate_hook_config(hook, HOOK_AFTER_SWAP, U256::from(MAX_HOOK_GAS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_has_permission() {
+        let config = HookConfig {
+            hook: Address::from([7u8; 20]),
+            permissions: HOOK_BEFORE_SWAP | HOOK_CAN_BLOCK,
+            gas_limit: U256::from(100000),
+        };
+
+        assert!(config.is_set());
+        assert!(config.has_permission(HOOK_BEFORE_SWAP));
+        assert!(config.has_permission(HOOK_CAN_BLOCK));
+        assert!(!config.has_permission(HOOK_AFTER_SWAP));
+    }
+}
@@ -1,5 +1,27 @@
 // AquaFlow Stylus Library
 pub mod secure_router;
+pub mod pool_registry;
 pub mod optimizations;
+pub mod nonces;
+pub mod tokens;
+pub mod limit_orders;
+pub mod dca;
+pub mod fees;
+pub mod guard;
+pub mod alerts;
+pub mod gas_meter;
+pub mod hooks;
+pub mod dynamic_fees;
+pub mod signatures;
+pub mod commit_reveal;
+pub mod batch_auction;
+pub mod solver_auction;
+pub mod uniswap_v2;
+pub mod uniswap_v3;
+pub mod curve;
+pub mod balancer;
+pub mod pool_adapter;
+pub mod pool_index;
+pub mod pool_stats;
 
-pub use secure_router::SecureAquaFlowRouter;
\ No newline at end of file
+pub use secure_router::SecureAquaFlowRouter;
@@ -0,0 +1,111 @@
+// AquaFlow Limit Orders - Escrowed intents filled by keepers at the user's limit price
+// The limit price is the ratio min_amount_out / amount_in of the parked ValidatedIntent
+
+use stylus_sdk::{
+    alloy_primitives::U256,
+    prelude::*,
+};
+
+use crate::secure_router::ValidatedIntent;
+
+/// Maximum keeper bounty (1%) taken from the filled output
+pub const MAX_KEEPER_BOUNTY_BPS: u64 = 100;
+
+/// Limit order parked in router storage with escrowed token_in
+#[derive(SolidityType, Clone)]
+pub struct LimitOrder {
+    pub intent: ValidatedIntent,   // Pair, size, limit price and expiry
+    pub filled_amount_in: U256,    // token_in already sold
+    pub total_amount_out: U256,    // token_out delivered to the user so far
+    pub is_open: bool,             // False once fully filled or cancelled; expired orders stay open until cancelled
+}
+
+impl LimitOrder {
+    /// Amount of escrowed token_in not yet filled
+    pub fn remaining(&self) -> U256 {
+        self.intent.amount_in - self.filled_amount_in
+    }
+}
+
+/// Whether a keeper may fill `fill_amount` of an order with `remaining` token_in left
+/// Every fill must meet `min_trade_amount` and leave either nothing or at least `min_trade_amount`,
+/// except the final fill of a remainder that is already smaller
+pub fn is_valid_fill(fill_amount: U256, remaining: U256, min_trade_amount: U256) -> bool {
+    if fill_amount == U256::ZERO || fill_amount > remaining {
+        return false;
+    }
+    if fill_amount == remaining {
+        return true;
+    }
+    fill_amount >= min_trade_amount && remaining - fill_amount >= min_trade_amount
+}
+
+/// Minimum output a partial fill must deliver to honour the limit price
+/// Rounds up so partial fills can never be worse than the limit
+pub fn min_out_for_fill(
+    min_amount_out: U256,
+    amount_in: U256,
+    fill_amount: U256
+) -> Result<U256, &'static str> {
+    if amount_in == U256::ZERO {
+        return Err("Empty order");
+    }
+
+    let numerator = min_amount_out.checked_mul(fill_amount)
+        .ok_or("Limit calculation overflow")?;
+
+    Ok(numerator.div_ceil(amount_in))
+}
+
+/// Split filled output into (user amount, keeper bounty)
+pub fn split_keeper_bounty(amount_out: U256, bounty_bps: U256) -> (U256, U256) {
+    let bounty = amount_out * bounty_bps / U256::from(10000);
+    (amount_out - bounty, bounty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_out_for_fill_pro_rata() {
+        let min_out = U256::from(2000);
+        let amount_in = U256::from(1000);
+
+        assert_eq!(min_out_for_fill(min_out, amount_in, amount_in), Ok(min_out));
+        assert_eq!(min_out_for_fill(min_out, amount_in, U256::from(250)), Ok(U256::from(500)));
+    }
+
+    #[test]
+    fn test_min_out_for_fill_rounds_up() {
+        // 1000 * 1 / 3 = 333.33 -> 334
+        let result = min_out_for_fill(U256::from(1000), U256::from(3), U256::from(1));
+        assert_eq!(result, Ok(U256::from(334)));
+    }
+
+    #[test]
+    fn test_is_valid_fill() {
+        let min_trade = U256::from(100);
+        let remaining = U256::from(1000);
+
+        assert!(is_valid_fill(U256::from(100), remaining, min_trade));
+        assert!(is_valid_fill(remaining, remaining, min_trade));
+
+        // Dust fills and fills that strand a dust remainder
+        assert!(!is_valid_fill(U256::from(99), remaining, min_trade));
+        assert!(!is_valid_fill(U256::from(901), remaining, min_trade));
+        assert!(!is_valid_fill(U256::ZERO, remaining, min_trade));
+        assert!(!is_valid_fill(U256::from(1001), remaining, min_trade));
+
+        // A remainder below the minimum can still be closed out in one fill
+        assert!(is_valid_fill(U256::from(40), U256::from(40), min_trade));
+        assert!(!is_valid_fill(U256::from(20), U256::from(40), min_trade));
+    }
+
+    #[test]
+    fn test_split_keeper_bounty() {
+        let (user, bounty) = split_keeper_bounty(U256::from(10000), U256::from(10));
+        assert_eq!(user, U256::from(9990));
+        assert_eq!(bounty, U256::from(10));
+    }
+}
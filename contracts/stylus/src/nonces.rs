@@ -0,0 +1,71 @@
+// AquaFlow Nonce Helpers - Sequential and unordered (bitmap) intent nonces
+// Unordered nonces follow the Permit2 layout so independent intents never block each other
+
+use stylus_sdk::alloy_primitives::U256;
+
+/// Maximum number of sequential nonces a user can skip in one call
+/// Prevents accidentally locking an account by jumping to U256::MAX
+pub const MAX_NONCE_INVALIDATION: u64 = 65535;
+
+/// Split an unordered nonce into its bitmap word position and bit mask
+/// Layout: [248 bits word position][8 bits bit position]
+pub fn bitmap_position(nonce: U256) -> (U256, U256) {
+    let word_pos = nonce >> 8;
+    let bit_pos = (nonce & U256::from(0xff)).to::<usize>();
+    (word_pos, U256::from(1) << bit_pos)
+}
+
+/// Check whether the nonce bit is already set in the given bitmap word
+pub fn is_bit_set(bitmap: U256, mask: U256) -> bool {
+    bitmap & mask != U256::ZERO
+}
+
+/// Validate a sequential nonce invalidation request
+/// Returns the new nonce if the jump is forward and within bounds
+pub fn checked_invalidation(current: U256, new_nonce: U256) -> Result<U256, &'static str> {
+    if new_nonce <= current {
+        return Err("Nonce must increase");
+    }
+
+    if new_nonce - current > U256::from(MAX_NONCE_INVALIDATION) {
+        return Err("Nonce jump too large");
+    }
+
+    Ok(new_nonce)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitmap_position() {
+        let (word, mask) = bitmap_position(U256::from(0));
+        assert_eq!(word, U256::ZERO);
+        assert_eq!(mask, U256::from(1));
+
+        let (word, mask) = bitmap_position(U256::from(513));
+        assert_eq!(word, U256::from(2));
+        assert_eq!(mask, U256::from(2));
+
+        let (word, mask) = bitmap_position(U256::from(255));
+        assert_eq!(word, U256::ZERO);
+        assert_eq!(mask, U256::from(1) << 255);
+    }
+
+    #[test]
+    fn test_bit_set() {
+        let (_, mask) = bitmap_position(U256::from(7));
+        assert!(!is_bit_set(U256::ZERO, mask));
+        assert!(is_bit_set(mask, mask));
+    }
+
+    #[test]
+    fn test_checked_invalidation() {
+        let current = U256::from(5);
+        assert!(checked_invalidation(current, U256::from(5)).is_err());
+        assert!(checked_invalidation(current, U256::from(4)).is_err());
+        assert_eq!(checked_invalidation(current, U256::from(10)), Ok(U256::from(10)));
+        assert!(checked_invalidation(current, current + U256::from(MAX_NONCE_INVALIDATION + 1)).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+// AquaFlow Pool Adapters - One implementation per AMM, looked up by PoolInfo.pool_type
+// Adding a DEX means writing an adapter module and listing it in ADAPTERS; the registry stays untouched
+
+use stylus_sdk::alloy_primitives::{keccak256, Address, U256};
+
+use crate::{balancer, curve, tokens, uniswap_v2, uniswap_v3};
+
+/// Fixed-point precision of spot prices (1e18 = 1 token_out raw unit per token_in raw unit)
+pub const PRICE_PRECISION: u64 = 1_000_000_000_000_000_000;
+
+/// Every registered adapter; pool types must be unique
+static ADAPTERS: [&dyn PoolAdapter; 4] = [
+    &uniswap_v2::UniswapV2Adapter,
+    &uniswap_v3::UniswapV3Adapter,
+    &curve::CurveAdapter,
+    &balancer::BalancerAdapter,
+];
+
+/// Pool as seen by its adapter: registry fields plus the adapter's own state words
+pub struct PoolView<'a> {
+    pub pool_address: Address,
+    pub token_a: Address,
+    pub token_b: Address,
+    pub reserve_a: U256,
+    pub reserve_b: U256,
+    pub fee_bps: U256,
+    pub state: &'a [U256],
+}
+
+impl PoolView<'_> {
+    /// (reserve_in, reserve_out) for the swap direction
+    pub fn reserves(&self, a_to_b: bool) -> (U256, U256) {
+        if a_to_b {
+            (self.reserve_a, self.reserve_b)
+        } else {
+            (self.reserve_b, self.reserve_a)
+        }
+    }
+
+    /// (token_in, token_out) for the swap direction
+    pub fn tokens(&self, a_to_b: bool) -> (Address, Address) {
+        if a_to_b {
+            (self.token_a, self.token_b)
+        } else {
+            (self.token_b, self.token_a)
+        }
+    }
+}
+
+/// Result of reading a pool contract
+pub struct PoolSnapshot {
+    pub reserve_a: U256,
+    pub reserve_b: U256,
+    pub state: Vec<U256>, // Adapter-specific state, stored by the registry as-is
+}
+
+/// Exact-input swap to encode
+pub struct SwapParams {
+    pub a_to_b: bool,
+    pub amount_in: U256,
+    pub min_amount_out: U256,
+    pub sender: Address,    // Account executing the call and paying token_in
+    pub recipient: Address,
+    pub deadline: U256,
+}
+
+/// Call that performs a swap
+pub struct SwapCall {
+    pub target: Address, // Pool, or the vault for vault-based AMMs
+    pub calldata: Vec<u8>,
+}
+
+/// How the pool collects token_in from the account executing a SwapCall
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SwapPayment {
+    Prepay,    // token_in is transferred to the pool before the call
+    Allowance, // The call target pulls token_in with transferFrom
+    Callback,  // The pool requests token_in through a callback during the call
+}
+
+/// AMM-specific reads, math and calldata
+pub trait PoolAdapter: Sync {
+    /// PoolInfo.pool_type served by this adapter
+    fn pool_type(&self) -> u8;
+
+    /// Read reserves in (token_a, token_b) order and the adapter's state from the pool contract
+    fn fetch_state(&self, pool_address: Address, token_a: Address, token_b: Address) -> Result<PoolSnapshot, Vec<u8>>;
+
+    /// Output for an exact input, fees included
+    fn quote_exact_in(&self, pool: &PoolView, a_to_b: bool, amount_in: U256) -> Result<U256, &'static str>;
+
+    /// Input needed for an exact output, fees included
+    /// Defaults to searching quote_exact_in; adapters with a closed form override it
+    fn quote_exact_out(&self, pool: &PoolView, a_to_b: bool, amount_out: U256) -> Result<U256, &'static str> {
+        solve_exact_out(|amount_in| self.quote_exact_in(pool, a_to_b, amount_in), amount_out)
+    }
+
+    /// Marginal price before fees, in PRICE_PRECISION units of token_out per token_in
+    fn spot_price(&self, pool: &PoolView, a_to_b: bool) -> Result<U256, &'static str>;
+
+    /// How the call from encode_swap is paid for
+    fn payment(&self) -> SwapPayment;
+
+    /// Call executing an exact-input swap of `swap.amount_in`
+    fn encode_swap(&self, pool: &PoolView, swap: &SwapParams) -> Result<SwapCall, &'static str>;
+
+    /// Every token the pool holds with its real balance, for TVL
+    /// Reserves can be virtual (V3) or cover only the registered pair, so they are not used.
+    /// Defaults to the pair's balances held by the pool contract itself
+    fn holdings(&self, pool: &PoolView) -> Result<Vec<(Address, U256)>, Vec<u8>> {
+        Ok(vec![
+            (pool.token_a, tokens::balance_of(pool.token_a, pool.pool_address)?),
+            (pool.token_b, tokens::balance_of(pool.token_b, pool.pool_address)?),
+        ])
+    }
+}
+
+/// Adapter registered for a pool type
+pub fn adapter_for(pool_type: u8) -> Result<&'static dyn PoolAdapter, &'static str> {
+    ADAPTERS.iter()
+        .find(|adapter| adapter.pool_type() == pool_type)
+        .copied()
+        .ok_or("Unsupported pool type")
+}
+
+/// Whether an adapter is registered for a pool type
+pub fn is_supported(pool_type: u8) -> bool {
+    adapter_for(pool_type).is_ok()
+}
+
+/// Smallest input whose quote covers `amount_out`, for monotonic quotes
+/// `quote` must fail once the input exceeds what the pool can absorb
+pub fn solve_exact_out(
+    quote: impl Fn(U256) -> Result<U256, &'static str>,
+    amount_out: U256
+) -> Result<U256, &'static str> {
+    if amount_out == U256::ZERO {
+        return Ok(U256::ZERO);
+    }
+
+    // Double until the quote covers the output, then bisect between the last two bounds
+    let mut low = U256::ZERO;
+    let mut high = amount_out;
+    while quote(high)? < amount_out {
+        low = high;
+        high = high.checked_mul(U256::from(2)).ok_or("Insufficient liquidity")?;
+    }
+
+    while high - low > U256::from(1) {
+        let mid = low + (high - low) / U256::from(2);
+        if quote(mid)? >= amount_out {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+
+    Ok(high)
+}
+
+/// Selector followed by ABI-encoded words
+pub fn encode_call(signature: &str, words: &[U256]) -> Vec<u8> {
+    let mut calldata = Vec::with_capacity(4 + 32 * words.len());
+    calldata.extend_from_slice(&keccak256(signature.as_bytes())[..4]);
+    for word in words {
+        calldata.extend_from_slice(&word.to_be_bytes::<32>());
+    }
+    calldata
+}
+
+/// State word holding an address
+pub fn address_word(address: Address) -> U256 {
+    U256::from_be_slice(address.as_slice())
+}
+
+/// Address stored by address_word
+pub fn word_address(word: U256) -> Address {
+    Address::from_slice(&word.to_be_bytes::<32>()[12..])
+}
+
+/// State word holding a signed integer (two's complement, as in the ABI)
+pub fn int_word(value: i128) -> U256 {
+    if value >= 0 {
+        U256::from(value as u128)
+    } else {
+        U256::ZERO - U256::from(value.unsigned_abs())
+    }
+}
+
+/// Signed integer stored by int_word
+pub fn word_int(word: U256) -> i128 {
+    (word & U256::from(u128::MAX)).to::<u128>() as i128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adapters_registered_once() {
+        for pool_type in [uniswap_v2::POOL_TYPE, uniswap_v3::POOL_TYPE, curve::POOL_TYPE, balancer::POOL_TYPE] {
+            assert_eq!(adapter_for(pool_type).unwrap().pool_type(), pool_type);
+            assert_eq!(ADAPTERS.iter().filter(|adapter| adapter.pool_type() == pool_type).count(), 1);
+        }
+        assert!(!is_supported(200));
+        assert!(adapter_for(200).is_err());
+    }
+
+    #[test]
+    fn test_solve_exact_out_matches_closed_form() {
+        let (reserve_in, reserve_out, fee) = (U256::from(1_000_000u64), U256::from(2_000_000u64), U256::from(30));
+        let quote = |amount_in| uniswap_v2::quote_exact_in(reserve_in, reserve_out, amount_in, fee);
+
+        for amount_out in [1u64, 999, 50_000, 1_500_000] {
+            let amount_out = U256::from(amount_out);
+            let searched = solve_exact_out(quote, amount_out).unwrap();
+            assert_eq!(searched, uniswap_v2::quote_exact_out(reserve_in, reserve_out, amount_out, fee).unwrap());
+            assert!(quote(searched).unwrap() >= amount_out);
+            assert!(quote(searched - U256::from(1)).unwrap() < amount_out);
+        }
+
+        // Quotes that run out of range end the search
+        let bounded = |amount_in: U256| if amount_in > U256::from(1000) { Err("Swap exceeds known tick range") } else { Ok(amount_in) };
+        assert_eq!(solve_exact_out(bounded, U256::from(1000)), Ok(U256::from(1000)));
+        assert_eq!(solve_exact_out(bounded, U256::from(1001)), Err("Swap exceeds known tick range"));
+    }
+
+    #[test]
+    fn test_state_words_round_trip() {
+        for value in [0i128, 1, -1, 887272, -887272, i128::MAX, i128::MIN] {
+            assert_eq!(word_int(int_word(value)), value);
+        }
+
+        let address = Address::from([7u8; 20]);
+        assert_eq!(word_address(address_word(address)), address);
+    }
+
+    #[test]
+    fn test_encode_call() {
+        let calldata = encode_call("transfer(address,uint256)", &[address_word(Address::from([1u8; 20])), U256::from(5)]);
+        assert_eq!(&calldata[..4], &[0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(calldata.len(), 68);
+        assert_eq!(calldata[67], 5);
+    }
+}
@@ -0,0 +1,220 @@
+// AquaFlow Pool Index - Id lookups and paginated scans shared by the registry and router
+// Pool ids start at 0; a pool exists iff its stored pool_address is non-zero, so no id doubles as "absent"
+
+use stylus_sdk::{
+    alloy_primitives::{Address, U256},
+    prelude::*,
+};
+
+/// Most results returned by one page
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// Most positions examined by one page, bounding view gas when few entries match
+pub const MAX_PAGE_SCAN: u64 = 1000;
+
+/// Criteria for PoolRegistry::list_pools
+#[derive(SolidityType, Clone)]
+pub struct PoolFilter {
+    pub chain_id: U256,        // Zero = any chain
+    pub pool_type: u8,
+    pub match_pool_type: bool, // Type 0 is a real pool type, so type filtering is opt-in
+    pub active_only: bool,
+    pub above_min_tvl: bool,   // Only pools whose TVL meets the registry's min_tvl_threshold
+    pub max_age_blocks: U256,  // Zero = any; otherwise only pools updated within this many blocks
+}
+
+/// Pool id registered for `pool_address`
+/// `indexed_id` is the address index entry (unset entries read as 0) and `stored_address`
+/// the address stored under that id; the entry only counts if they agree
+pub fn resolve_address(indexed_id: U256, stored_address: Address, pool_address: Address) -> Option<U256> {
+    (pool_address != Address::ZERO && stored_address == pool_address).then_some(indexed_id)
+}
+
+/// Id to return for an index list entry, or None to skip an inactive pool
+pub fn active_entry(pool_id: U256, is_active: bool) -> Option<U256> {
+    is_active.then_some(pool_id)
+}
+
+/// Swap-remove `pool_id` from a pair or chain index list of `len` entries read through `get`
+/// Returns (position, moved_id): the caller writes moved_id (the last entry) at position and drops
+/// the last slot, so the list shrinks by one. None if the pool is not listed.
+/// A paging walk already past `position` misses the moved pool; no pool is ever returned twice
+pub fn swap_remove(len: usize, pool_id: U256, get: impl Fn(usize) -> Option<U256>) -> Option<(usize, U256)> {
+    let position = (0..len).find(|&i| get(i) == Some(pool_id))?;
+    let last = get(len - 1)?;
+    Some((position, last))
+}
+
+/// Candidate with the highest non-zero output; earlier candidates win ties
+pub fn best_output<T>(candidates: impl IntoIterator<Item = (U256, T)>) -> Option<(U256, T)> {
+    let mut best: Option<(U256, T)> = None;
+
+    for (output, candidate) in candidates {
+        let current = best.as_ref().map(|(best_output, _)| *best_output).unwrap_or(U256::ZERO);
+        if output > current {
+            best = Some((output, candidate));
+        }
+    }
+
+    best
+}
+
+/// One page of a scan over positions [cursor, len)
+/// `visit` maps a position to the id to return, or None to skip it
+/// Returns (ids, next_cursor, has_more); pass next_cursor back while has_more is set
+pub fn scan_page(
+    cursor: U256,
+    limit: U256,
+    len: U256,
+    mut visit: impl FnMut(U256) -> Option<U256>
+) -> Result<(Vec<U256>, U256, bool), &'static str> {
+    if limit == U256::ZERO {
+        return Err("Invalid page size");
+    }
+
+    let limit = limit.min(U256::from(MAX_PAGE_SIZE)).to::<usize>();
+    let end = len.min(cursor.saturating_add(U256::from(MAX_PAGE_SCAN)));
+
+    let mut ids = Vec::new();
+    let mut position = cursor;
+    while position < end && ids.len() < limit {
+        if let Some(id) = visit(position) {
+            ids.push(id);
+        }
+        position += U256::from(1);
+    }
+
+    Ok((ids, position, position < len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_pool_resolves_by_address() {
+        let first = Address::from([1u8; 20]);
+        let unknown = Address::from([2u8; 20]);
+
+        // Pool 0 is found, and its id does not make unknown addresses look registered
+        assert_eq!(resolve_address(U256::ZERO, first, first), Some(U256::ZERO));
+        assert_eq!(resolve_address(U256::ZERO, first, unknown), None);
+        assert_eq!(resolve_address(U256::from(3), unknown, unknown), Some(U256::from(3)));
+
+        // Empty registry
+        assert_eq!(resolve_address(U256::ZERO, Address::ZERO, unknown), None);
+        assert_eq!(resolve_address(U256::ZERO, Address::ZERO, Address::ZERO), None);
+    }
+
+    #[test]
+    fn test_best_output_can_pick_first_pool() {
+        let candidates = [(U256::from(500), U256::ZERO), (U256::from(400), U256::from(1))];
+        assert_eq!(best_output(candidates), Some((U256::from(500), U256::ZERO)));
+
+        // Ties keep the earlier pool; zero outputs never win
+        let tied = [(U256::from(7), U256::from(2)), (U256::from(7), U256::from(5))];
+        assert_eq!(best_output(tied), Some((U256::from(7), U256::from(2))));
+        assert_eq!(best_output([(U256::ZERO, U256::ZERO)]), None);
+        assert_eq!(best_output(Vec::<(U256, U256)>::new()), None);
+    }
+
+    #[test]
+    fn test_scan_page_walks_every_position() {
+        // Even ids match; pages of 3 starting from pool 0
+        let even = |id: U256| (id % U256::from(2) == U256::ZERO).then_some(id);
+        let len = U256::from(10);
+
+        let (ids, cursor, has_more) = scan_page(U256::ZERO, U256::from(3), len, even).unwrap();
+        assert_eq!(ids, vec![U256::ZERO, U256::from(2), U256::from(4)]);
+        assert!(has_more);
+
+        let (ids, cursor, has_more) = scan_page(cursor, U256::from(3), len, even).unwrap();
+        assert_eq!(ids, vec![U256::from(6), U256::from(8)]);
+        assert_eq!(cursor, len);
+        assert!(!has_more);
+
+        assert_eq!(scan_page(U256::ZERO, U256::ZERO, len, even), Err("Invalid page size"));
+    }
+
+    /// Apply a swap_remove to an in-memory list the way the registry applies it to storage
+    fn remove(list: &mut Vec<U256>, pool_id: U256) -> bool {
+        match swap_remove(list.len(), pool_id, |i| list.get(i).copied()) {
+            Some((position, moved)) => {
+                list[position] = moved;
+                list.pop();
+                true
+            },
+            None => false,
+        }
+    }
+
+    #[test]
+    fn test_swap_remove_shrinks_list() {
+        let mut list: Vec<U256> = (0..5u64).map(U256::from).collect();
+
+        assert!(remove(&mut list, U256::from(1)));
+        assert_eq!(list, vec![U256::ZERO, U256::from(4), U256::from(2), U256::from(3)]);
+
+        // Last entry and then every other entry
+        assert!(remove(&mut list, U256::from(3)));
+        assert_eq!(list, vec![U256::ZERO, U256::from(4), U256::from(2)]);
+        for id in [0u64, 4, 2] {
+            assert!(remove(&mut list, U256::from(id)));
+        }
+        assert!(list.is_empty());
+
+        // Unlisted pools leave the list alone
+        assert!(!remove(&mut list, U256::from(7)));
+        let mut list = vec![U256::from(9)];
+        assert!(!remove(&mut list, U256::from(7)));
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn test_removal_between_pages() {
+        // Index list of pools 0..6; pool 1 is removed after the first page was read
+        let mut list: Vec<U256> = (0..6u64).map(U256::from).collect();
+        let (ids, cursor, _) = scan_page(U256::ZERO, U256::from(3), U256::from(list.len()), |i| {
+            active_entry(list[i.to::<usize>()], true)
+        }).unwrap();
+        assert_eq!(ids, vec![U256::ZERO, U256::from(1), U256::from(2)]);
+
+        assert!(remove(&mut list, U256::from(1)));
+        let len = U256::from(list.len());
+        let visit = |i: U256| active_entry(list[i.to::<usize>()], true);
+
+        // Pool 5 moved behind the cursor and is missed by this walk; nothing repeats
+        let (ids, _, has_more) = scan_page(cursor, U256::from(3), len, visit).unwrap();
+        assert_eq!(ids, vec![U256::from(3), U256::from(4)]);
+        assert!(!has_more);
+
+        // A fresh scan sees every remaining pool
+        let (ids, _, _) = scan_page(U256::ZERO, U256::from(10), len, visit).unwrap();
+        assert_eq!(ids.len(), 5);
+        assert!(!ids.contains(&U256::from(1)));
+    }
+
+    #[test]
+    fn test_active_entry() {
+        assert_eq!(active_entry(U256::ZERO, true), Some(U256::ZERO));
+        assert_eq!(active_entry(U256::from(2), false), None);
+    }
+
+    #[test]
+    fn test_scan_page_bounds_gas() {
+        // Nothing matches: the page stops after MAX_PAGE_SCAN positions and hands back a cursor
+        let len = U256::from(MAX_PAGE_SCAN * 3);
+        let (ids, cursor, has_more) = scan_page(U256::ZERO, U256::from(10), len, |_| None).unwrap();
+        assert!(ids.is_empty());
+        assert_eq!(cursor, U256::from(MAX_PAGE_SCAN));
+        assert!(has_more);
+
+        // Oversized limits are clamped
+        let (ids, _, _) = scan_page(U256::ZERO, U256::MAX, len, Some).unwrap();
+        assert_eq!(ids.len() as u64, MAX_PAGE_SIZE);
+
+        // Cursors past the end return an empty, final page
+        let (ids, _, has_more) = scan_page(len + U256::from(5), U256::from(10), len, Some).unwrap();
+        assert!(ids.is_empty() && !has_more);
+    }
+}
@@ -1,13 +1,22 @@
 // AquaFlow Pool Registry - Minimal onchain liquidity source tracking
-// Hackathon-safe with simulated liquidity, production-ready architecture
+// Reserves read onchain per AMM type, production-ready architecture
+
+extern crate alloc;
 
 use stylus_sdk::{
+    abi::Bytes,
     alloy_primitives::{Address, U256},
     prelude::*,
     storage::{StorageMap, StorageU256, StorageVec, StorageAddress, StorageBool},
     evm,
 };
 
+use crate::guard::NonReentrant;
+use crate::pool_adapter::{self, PoolView, SwapParams};
+use crate::pool_index::{self, PoolFilter, MAX_PAGE_SIZE};
+use crate::pool_stats::{self, VolumeWindow, IMPACT_PROBE_USD};
+use crate::tokens;
+
 /// Pool metadata optimized for reads
 /// Packed storage to minimize gas costs
 #[derive(SolidityType, Clone)]
@@ -18,20 +27,35 @@ pub struct PoolInfo {
     pub reserve_a: U256,
     pub reserve_b: U256,
     pub fee_bps: U256,        // Fee in basis points (30 = 0.3%)
-    pub pool_type: u8,        // 0=Uniswap-v2, 1=Uniswap-v3, 2=Curve, 3=Balancer
+    pub pool_type: u8,        // Adapter type id, see pool_adapter::adapter_for
     pub chain_id: U256,       // Chain where pool exists
     pub is_active: bool,      // Pool status
     pub last_updated: U256,   // Block number of last update
 }
 
+impl PoolInfo {
+    /// View handed to the pool's adapter
+    pub fn view<'a>(&self, state: &'a [U256]) -> PoolView<'a> {
+        PoolView {
+            pool_address: self.pool_address,
+            token_a: self.token_a,
+            token_b: self.token_b,
+            reserve_a: self.reserve_a,
+            reserve_b: self.reserve_b,
+            fee_bps: self.fee_bps,
+            state,
+        }
+    }
+}
+
 /// Pool statistics for routing optimization
 #[derive(SolidityType, Clone)]
 pub struct PoolStats {
-    pub tvl_usd: U256,           // Total Value Locked in USD
-    pub volume_24h_usd: U256,    // 24h volume in USD
+    pub tvl_usd: U256,           // Total Value Locked in whole USD, from oracle prices
+    pub volume_24h_usd: U256,    // 24h volume in USD, from reported swaps
     pub fees_24h_usd: U256,      // 24h fees collected
-    pub price_impact_1k: U256,   // Price impact for $1k swap (bps)
-    pub utilization_rate: U256,  // How much liquidity is being used (bps)
+    pub price_impact_1k: U256,   // Price impact for $1k swap of token_a, fees excluded (bps)
+    pub utilization_rate: U256,  // 24h volume over TVL (bps)
 }
 
 /// Token pair for efficient lookups
@@ -78,6 +102,21 @@ sol_storage! {
         // Emergency controls
         paused: StorageBool,
         emergency_admin: StorageAddress,
+        reentrancy_locked: StorageBool,
+        
+        // Adapter state
+        pool_states: StorageMap<U256, StorageVec<U256>>, // pool_id -> adapter state words
+        
+        // Active pool counts
+        active_pool_count: StorageU256,              // Active pools with id < active_count_cursor
+        active_pools_by_chain: StorageMap<U256, U256>, // chain_id -> active pools
+        active_pools_by_type: StorageMap<u8, U256>,  // pool_type -> active pools
+        active_count_cursor: StorageU256,            // Pools below this id are reflected in the active counts
+        
+        // Pool stats
+        price_oracle: StorageAddress,                // USD price source for stats
+        max_price_age: StorageU256,                  // Oldest oracle answer accepted, in seconds
+        volume_windows: StorageMap<U256, VolumeWindow>, // pool_id -> reported swap volume
     }
 }
 
@@ -105,6 +144,27 @@ sol! {
         string reason
     );
     
+    event PoolReactivated(
+        uint256 indexed pool_id
+    );
+    
+    event PoolRemoved(
+        uint256 indexed pool_id,
+        address indexed pool_address
+    );
+    
+    event SwapReported(
+        uint256 indexed pool_id,
+        address indexed token_in,
+        uint256 amount_in,
+        uint256 volume_usd
+    );
+    
+    event PriceOracleUpdated(
+        address indexed oracle,
+        uint256 max_price_age
+    );
+    
     event RegistryConfigUpdated(
         uint256 min_tvl_threshold,
         uint256 max_price_impact,
@@ -121,15 +181,19 @@ impl PoolRegistry {
         min_tvl: U256,
         max_impact: U256
     ) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
         self.owner.set(owner);
         self.min_tvl_threshold.set(min_tvl);
         self.max_price_impact.set(max_impact);
         self.update_frequency.set(U256::from(100)); // 100 blocks (~20 minutes)
+        self.max_price_age.set(U256::from(3600));   // 1 hour
         self.paused.set(false);
         
         // Add owner as authorized updater
         self.authorized_updaters.setter(owner).set(true);
         
+        self.exit_non_reentrant();
         Ok(())
     }
     
@@ -143,6 +207,8 @@ impl PoolRegistry {
         pool_type: u8,
         chain_id: U256
     ) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
         // Access control
         if !self.is_authorized_updater(msg::sender()) {
             return Err(b"Unauthorized".to_vec());
@@ -162,15 +228,14 @@ impl PoolRegistry {
         }
         
         // Check if pool already exists
-        let existing_pool_id = self.address_to_pool.get(pool_address);
-        if existing_pool_id != U256::ZERO {
+        if self.find_pool_by_address(pool_address).is_some() {
             return Err(b"Pool already exists".to_vec());
         }
         
         let pool_id = self.pool_count.get();
         
-        // Get initial reserves (hackathon: simulated, production: call pool contract)
-        let (reserve_a, reserve_b) = self.get_pool_reserves_hackathon(pool_address, token_a, token_b)?;
+        // Get initial reserves from the pool contract
+        let (reserve_a, reserve_b) = self.sync_pool_state(pool_id, pool_type, pool_address, token_a, token_b)?;
         
         // Create pool info
         let pool_info = PoolInfo {
@@ -186,6 +251,9 @@ impl PoolRegistry {
             last_updated: U256::from(block::number()),
         };
         
+        // Tokens the oracle cannot price yet start with empty stats, filled in by update_pool
+        let initial_stats = self.compute_pool_stats(pool_id, &pool_info);
+        
         // Store pool data
         self.pools.setter(pool_id).set(pool_info);
         self.address_to_pool.setter(pool_address).set(pool_id);
@@ -198,13 +266,19 @@ impl PoolRegistry {
         let mut chain_pools = self.chain_pools.setter(chain_id);
         chain_pools.push(pool_id);
         
-        // Initialize stats (hackathon: simulated data)
-        let pool_stats = self.generate_hackathon_stats(reserve_a, reserve_b, fee_bps);
-        self.pool_stats.setter(pool_id).set(pool_stats);
+        if let Ok(stats) = initial_stats {
+            self.pool_stats.setter(pool_id).set(stats);
+        }
         
         // Increment counter
         self.pool_count.set(pool_id + U256::from(1));
         
+        // Count the pool now unless older pools still await sync_active_pool_counts
+        if self.active_count_cursor.get() == pool_id {
+            self.active_count_cursor.set(pool_id + U256::from(1));
+            self.count_active_change(chain_id, pool_type, true);
+        }
+        
         evm::log(PoolAdded {
             pool_id,
             pool_address,
@@ -214,11 +288,14 @@ impl PoolRegistry {
             pool_type,
         });
         
+        self.exit_non_reentrant();
         Ok(pool_id)
     }
     
     /// Update pool reserves and statistics
     pub fn update_pool(&mut self, pool_id: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
         if !self.is_authorized_updater(msg::sender()) {
             return Err(b"Unauthorized".to_vec());
         }
@@ -234,8 +311,10 @@ impl PoolRegistry {
             return Err(b"Update too frequent".to_vec());
         }
         
-        // Get fresh reserves (hackathon: simulated, production: call pool contract)
-        let (new_reserve_a, new_reserve_b) = self.get_pool_reserves_hackathon(
+        // Get fresh reserves from the pool contract
+        let (new_reserve_a, new_reserve_b) = self.sync_pool_state(
+            pool_id,
+            pool.pool_type,
             pool.pool_address,
             pool.token_a,
             pool.token_b
@@ -246,11 +325,15 @@ impl PoolRegistry {
         pool.reserve_b = new_reserve_b;
         pool.last_updated = U256::from(block::number());
         
+        // Update statistics; without fresh prices the previous stats stand and TVL is not rechecked
+        let priced = self.compute_pool_stats(pool_id, &pool).ok();
+        if let Some(stats) = priced.clone() {
+            self.pool_stats.setter(pool_id).set(stats);
+        }
+        
         self.pools.setter(pool_id).set(pool);
         
-        // Update statistics
-        let new_stats = self.generate_hackathon_stats(new_reserve_a, new_reserve_b, pool.fee_bps);
-        self.pool_stats.setter(pool_id).set(new_stats);
+        let new_stats = self.get_pool_stats(pool_id);
         
         evm::log(PoolUpdated {
             pool_id,
@@ -260,10 +343,17 @@ impl PoolRegistry {
             volume_24h: new_stats.volume_24h_usd,
         });
         
+        // Drained pools drop out of routing until an owner reactivates them
+        if priced.is_some() && new_stats.tvl_usd < self.min_tvl_threshold.get() {
+            self.set_pool_inactive(pool_id, "TVL below threshold");
+        }
+        
+        self.exit_non_reentrant();
         Ok(())
     }
     
     /// Get pools for token pair (optimized for routing)
+    /// Returns every active pool; use get_pools_for_pair_page for large pairs
     pub fn get_pools_for_pair(
         &self,
         token_a: Address,
@@ -277,8 +367,7 @@ impl PoolRegistry {
         let len = pair_pools.len();
         
         for i in 0..len {
-            let pool_id = pair_pools.get(i).unwrap_or(U256::ZERO);
-            if pool_id != U256::ZERO {
+            if let Some(pool_id) = pair_pools.get(i) {
                 let pool = self.pools.get(pool_id);
                 if pool.is_active {
                     result.push(pool_id);
@@ -289,25 +378,75 @@ impl PoolRegistry {
         result
     }
     
+    /// Page through active pools for a token pair; `cursor` starts at 0
+    /// Returns (pool_ids, next_cursor, has_more)
+    pub fn get_pools_for_pair_page(
+        &self,
+        token_a: Address,
+        token_b: Address,
+        cursor: U256,
+        limit: U256
+    ) -> Result<(Vec<U256>, U256, bool), Vec<u8>> {
+        let pair_pools = self.pair_to_pools.get(TokenPair::new(token_a, token_b));
+        
+        pool_index::scan_page(cursor, limit, U256::from(pair_pools.len()), |i| {
+            pair_pools.get(i.to::<usize>()).and_then(|pool_id| self.active_entry(pool_id))
+        }).map_err(|e| e.as_bytes().to_vec())
+    }
+    
+    /// Page through every pool id matching `filter`; `cursor` starts at 0
+    /// Returns (pool_ids, next_cursor, has_more)
+    pub fn list_pools(&self, filter: PoolFilter, cursor: U256, limit: U256) -> Result<(Vec<U256>, U256, bool), Vec<u8>> {
+        pool_index::scan_page(cursor, limit, self.pool_count.get(), |pool_id| {
+            self.matches_filter(pool_id, &filter).then_some(pool_id)
+        }).map_err(|e| e.as_bytes().to_vec())
+    }
+    
     /// Get pool info by ID
     pub fn get_pool_info(&self, pool_id: U256) -> PoolInfo {
         self.pools.get(pool_id)
     }
     
+    /// Get info for several pools in one call, in the order requested
+    pub fn get_pools_info(&self, pool_ids: Vec<U256>) -> Result<Vec<PoolInfo>, Vec<u8>> {
+        if pool_ids.len() as u64 > MAX_PAGE_SIZE {
+            return Err(b"Too many pools".to_vec());
+        }
+        
+        Ok(pool_ids.iter().map(|pool_id| self.pools.get(*pool_id)).collect())
+    }
+    
+    /// Get the id of a registered pool contract
+    pub fn get_pool_id(&self, pool_address: Address) -> Result<U256, Vec<u8>> {
+        self.find_pool_by_address(pool_address).ok_or(b"Pool not found".to_vec())
+    }
+    
     /// Get pool statistics
+    /// TVL and price impact are as of the last update; volume, fees and utilization are current
     pub fn get_pool_stats(&self, pool_id: U256) -> PoolStats {
-        self.pool_stats.get(pool_id)
+        let mut stats = self.pool_stats.get(pool_id);
+        let (volume, fees) = pool_stats::rolling_volume(&self.volume_windows.get(pool_id), U256::from(block::timestamp()));
+        
+        stats.volume_24h_usd = volume;
+        stats.fees_24h_usd = fees;
+        stats.utilization_rate = pool_stats::utilization_bps(volume, stats.tvl_usd);
+        stats
+    }
+    
+    /// Get the price oracle and the oldest price it may answer with, in seconds
+    pub fn get_price_oracle(&self) -> (Address, U256) {
+        (self.price_oracle.get(), self.max_price_age.get())
     }
     
     /// Get pools on specific chain
+    /// Returns every active pool; use get_pools_by_chain_page for large chains
     pub fn get_pools_by_chain(&self, chain_id: U256) -> Vec<U256> {
         let chain_pools = self.chain_pools.get(chain_id);
         let mut result = Vec::new();
         let len = chain_pools.len();
         
         for i in 0..len {
-            let pool_id = chain_pools.get(i).unwrap_or(U256::ZERO);
-            if pool_id != U256::ZERO {
+            if let Some(pool_id) = chain_pools.get(i) {
                 let pool = self.pools.get(pool_id);
                 if pool.is_active {
                     result.push(pool_id);
@@ -318,11 +457,115 @@ impl PoolRegistry {
         result
     }
     
+    /// Page through active pools on a chain; `cursor` starts at 0
+    /// Returns (pool_ids, next_cursor, has_more)
+    pub fn get_pools_by_chain_page(
+        &self,
+        chain_id: U256,
+        cursor: U256,
+        limit: U256
+    ) -> Result<(Vec<U256>, U256, bool), Vec<u8>> {
+        let chain_pools = self.chain_pools.get(chain_id);
+        
+        pool_index::scan_page(cursor, limit, U256::from(chain_pools.len()), |i| {
+            chain_pools.get(i.to::<usize>()).and_then(|pool_id| self.active_entry(pool_id))
+        }).map_err(|e| e.as_bytes().to_vec())
+    }
+    
+    /// Quote an exact-input swap through a pool using its AMM's math
+    pub fn quote_exact_in(&self, pool_id: U256, token_in: Address, amount_in: U256) -> Result<U256, Vec<u8>> {
+        let (pool, state, a_to_b) = self.load_swap_pool(pool_id, token_in)?;
+        let adapter = pool_adapter::adapter_for(pool.pool_type).map_err(|e| e.as_bytes().to_vec())?;
+        
+        adapter.quote_exact_in(&pool.view(&state), a_to_b, amount_in)
+            .map_err(|e| e.as_bytes().to_vec())
+    }
+    
+    /// Quote the input needed for an exact output through a pool
+    pub fn quote_exact_out(&self, pool_id: U256, token_in: Address, amount_out: U256) -> Result<U256, Vec<u8>> {
+        let (pool, state, a_to_b) = self.load_swap_pool(pool_id, token_in)?;
+        let adapter = pool_adapter::adapter_for(pool.pool_type).map_err(|e| e.as_bytes().to_vec())?;
+        
+        adapter.quote_exact_out(&pool.view(&state), a_to_b, amount_out)
+            .map_err(|e| e.as_bytes().to_vec())
+    }
+    
+    /// Marginal price of token_in in the other token (1e18 = 1:1 in raw units)
+    pub fn get_spot_price(&self, pool_id: U256, token_in: Address) -> Result<U256, Vec<u8>> {
+        let (pool, state, a_to_b) = self.load_swap_pool(pool_id, token_in)?;
+        let adapter = pool_adapter::adapter_for(pool.pool_type).map_err(|e| e.as_bytes().to_vec())?;
+        
+        adapter.spot_price(&pool.view(&state), a_to_b)
+            .map_err(|e| e.as_bytes().to_vec())
+    }
+    
+    /// Target and calldata for an exact-input swap executed by the caller
+    pub fn get_swap_call(
+        &self,
+        pool_id: U256,
+        token_in: Address,
+        amount_in: U256,
+        min_amount_out: U256,
+        recipient: Address,
+        deadline: U256
+    ) -> Result<(Address, Bytes), Vec<u8>> {
+        let (pool, state, a_to_b) = self.load_swap_pool(pool_id, token_in)?;
+        let adapter = pool_adapter::adapter_for(pool.pool_type).map_err(|e| e.as_bytes().to_vec())?;
+        
+        let swap = SwapParams {
+            a_to_b,
+            amount_in,
+            min_amount_out,
+            sender: msg::sender(),
+            recipient,
+            deadline,
+        };
+        let call = adapter.encode_swap(&pool.view(&state), &swap)
+            .map_err(|e| e.as_bytes().to_vec())?;
+        
+        Ok((call.target, Bytes::from(call.calldata)))
+    }
+    
+    /// Get the raw adapter state words of a pool as of its last update; quotes re-read live state
+    /// Their layout is the pool type's; decode them with its adapter's decode_state
+    pub fn get_pool_state(&self, pool_id: U256) -> Vec<U256> {
+        self.load_state(pool_id)
+    }
+    
     /// Get total number of active pools
     pub fn get_active_pool_count(&self) -> U256 {
-        // Simplified for hackathon - would iterate and count active pools in production
+        self.active_pool_count.get()
+    }
+    
+    /// Get number of active pools on a chain
+    pub fn get_active_pool_count_by_chain(&self, chain_id: U256) -> U256 {
+        self.active_pools_by_chain.get(chain_id)
+    }
+    
+    /// Get number of active pools of an AMM type
+    pub fn get_active_pool_count_by_type(&self, pool_type: u8) -> U256 {
+        self.active_pools_by_type.get(pool_type)
+    }
+    
+    /// Get number of pool ids issued, including inactive and removed pools
+    pub fn get_pool_count(&self) -> U256 {
         self.pool_count.get()
     }
+    
+    /// Whether the active counts cover every pool (false until sync_active_pool_counts catches up)
+    pub fn are_active_counts_synced(&self) -> bool {
+        self.active_count_cursor.get() == self.pool_count.get()
+    }
+}
+
+impl NonReentrant for PoolRegistry {
+    fn reentrancy_locked(&self) -> bool {
+        self.reentrancy_locked.get()
+    }
+    
+    fn set_reentrancy_locked(&mut self, locked: bool) {
+        self.reentrancy_locked.set(locked);
+    }
 }
 
 // Internal helper functions
@@ -331,59 +574,187 @@ impl PoolRegistry {
         user == self.owner.get() || self.authorized_updaters.get(user)
     }
     
-    /// Hackathon: Generate simulated pool reserves
-    /// Production: Call actual pool contracts to get real reserves
-    fn get_pool_reserves_hackathon(
-        &self,
-        _pool_address: Address,
-        _token_a: Address,
-        _token_b: Address
+    /// Id of the pool registered at `pool_address`
+    /// address_to_pool reads 0 for unknown addresses, so the entry is checked against the pool it names
+    fn find_pool_by_address(&self, pool_address: Address) -> Option<U256> {
+        let pool_id = self.address_to_pool.get(pool_address);
+        pool_index::resolve_address(pool_id, self.pools.get(pool_id).pool_address, pool_address)
+    }
+    
+    /// Read pool state through the adapter for its AMM type and store the adapter's state words
+    /// Returns reserves in (token_a, token_b) order; V3 pools report active-range virtual reserves
+    fn sync_pool_state(
+        &mut self,
+        pool_id: U256,
+        pool_type: u8,
+        pool_address: Address,
+        token_a: Address,
+        token_b: Address
     ) -> Result<(U256, U256), Vec<u8>> {
-        // Simulated reserves for hackathon demo
-        // In production, this would call:
-        // - Uniswap V2: getReserves()
-        // - Uniswap V3: slot0() + liquidity calculations
-        // - Curve: get_balances()
-        // - Balancer: getPoolTokens()
+        let adapter = pool_adapter::adapter_for(pool_type).map_err(|e| e.as_bytes().to_vec())?;
+        let snapshot = adapter.fetch_state(pool_address, token_a, token_b)?;
         
-        let base_reserve = U256::from(1000000) * U256::from(10).pow(U256::from(18));
-        let reserve_a = base_reserve + U256::from(block::number() % 100000) * U256::from(10).pow(U256::from(15));
-        let reserve_b = base_reserve + U256::from((block::number() * 7) % 100000) * U256::from(10).pow(U256::from(15));
+        let mut words = self.pool_states.setter(pool_id);
+        while words.len() > snapshot.state.len() {
+            words.pop();
+        }
+        for (i, word) in snapshot.state.iter().enumerate() {
+            match words.setter(i) {
+                Some(mut slot) => slot.set(*word),
+                None => words.push(*word),
+            }
+        }
         
-        Ok((reserve_a, reserve_b))
+        Ok((snapshot.reserve_a, snapshot.reserve_b))
     }
     
-    /// Hackathon: Generate realistic pool statistics
-    /// Production: Calculate from onchain data and price feeds
-    fn generate_hackathon_stats(&self, reserve_a: U256, reserve_b: U256, fee_bps: U256) -> PoolStats {
-        // Simulate realistic TVL (assuming $1 per token for simplicity)
-        let tvl_usd = (reserve_a + reserve_b) / U256::from(10).pow(U256::from(18));
+    /// Mark a pool inactive and log why; pools already inactive are left untouched
+    fn set_pool_inactive(&mut self, pool_id: U256, reason: &str) {
+        let mut pool = self.pools.get(pool_id);
+        if !pool.is_active {
+            return;
+        }
         
-        // Simulate 24h volume as 10-50% of TVL
-        let volume_multiplier = 10 + (block::number() % 40);
-        let volume_24h_usd = tvl_usd * U256::from(volume_multiplier) / U256::from(100);
+        pool.is_active = false;
+        self.pools.setter(pool_id).set(pool);
         
-        // Calculate fees from volume
-        let fees_24h_usd = volume_24h_usd * fee_bps / U256::from(10000);
+        if pool_id < self.active_count_cursor.get() {
+            self.count_active_change(pool.chain_id, pool.pool_type, false);
+        }
         
-        // Simulate price impact for $1k swap
-        let swap_amount = U256::from(1000) * U256::from(10).pow(U256::from(18));
-        let price_impact_1k = if reserve_a > swap_amount {
-            (swap_amount * U256::from(10000)) / reserve_a // Simplified impact calculation
-        } else {
-            U256::from(1000) // 10% max impact
+        evm::log(PoolDeactivated {
+            pool_id,
+            reason: String::from(reason),
+        });
+    }
+    
+    /// Apply one activation or deactivation to the overall, per-chain and per-type active counts
+    fn count_active_change(&mut self, chain_id: U256, pool_type: u8, activated: bool) {
+        let step = |count: U256| if activated { count + U256::from(1) } else { count.saturating_sub(U256::from(1)) };
+        
+        self.active_pool_count.set(step(self.active_pool_count.get()));
+        let chain_count = self.active_pools_by_chain.get(chain_id);
+        self.active_pools_by_chain.setter(chain_id).set(step(chain_count));
+        let type_count = self.active_pools_by_type.get(pool_type);
+        self.active_pools_by_type.setter(pool_type).set(step(type_count));
+    }
+    
+    /// Index entry for a paged scan, see pool_index::active_entry
+    fn active_entry(&self, pool_id: U256) -> Option<U256> {
+        pool_index::active_entry(pool_id, self.pools.get(pool_id).is_active)
+    }
+    
+    /// Drop a pool id from a pair or chain index list
+    fn unlist(list: &mut StorageVec<StorageU256>, pool_id: U256) {
+        if let Some((position, moved)) = pool_index::swap_remove(list.len(), pool_id, |i| list.get(i)) {
+            if let Some(mut slot) = list.setter(position) {
+                slot.set(moved);
+            }
+            list.erase_last();
+        }
+    }
+    
+    /// Whether a registered pool passes a list_pools filter
+    fn matches_filter(&self, pool_id: U256, filter: &PoolFilter) -> bool {
+        let pool = self.pools.get(pool_id);
+        if pool.pool_address == Address::ZERO {
+            return false;
+        }
+        
+        if filter.active_only && !pool.is_active {
+            return false;
+        }
+        
+        if filter.chain_id != U256::ZERO && pool.chain_id != filter.chain_id {
+            return false;
+        }
+        
+        if filter.match_pool_type && pool.pool_type != filter.pool_type {
+            return false;
+        }
+        
+        if filter.max_age_blocks != U256::ZERO
+            && U256::from(block::number()).saturating_sub(pool.last_updated) > filter.max_age_blocks {
+            return false;
+        }
+        
+        !filter.above_min_tvl || self.pool_stats.get(pool_id).tvl_usd >= self.min_tvl_threshold.get()
+    }
+    
+    /// Adapter state words of a pool
+    fn load_state(&self, pool_id: U256) -> Vec<U256> {
+        let words = self.pool_states.get(pool_id);
+        (0..words.len()).filter_map(|i| words.get(i)).collect()
+    }
+    
+    /// Active pool with live reserves, its live state words and the swap direction for `token_in`
+    /// State is re-read from the pool contract rather than taken from the last update_pool, so quotes
+    /// track the current price and a V3 quote's tick range is the one around the current tick
+    fn load_swap_pool(&self, pool_id: U256, token_in: Address) -> Result<(PoolInfo, Vec<U256>, bool), Vec<u8>> {
+        let mut pool = self.pools.get(pool_id);
+        if pool.pool_address == Address::ZERO || !pool.is_active {
+            return Err(b"Pool not found".to_vec());
+        }
+        
+        if token_in != pool.token_a && token_in != pool.token_b {
+            return Err(b"Token not in pool".to_vec());
+        }
+        
+        let adapter = pool_adapter::adapter_for(pool.pool_type).map_err(|e| e.as_bytes().to_vec())?;
+        let snapshot = adapter.fetch_state(pool.pool_address, pool.token_a, pool.token_b)?;
+        pool.reserve_a = snapshot.reserve_a;
+        pool.reserve_b = snapshot.reserve_b;
+        
+        let a_to_b = token_in == pool.token_a;
+        Ok((pool, snapshot.state, a_to_b))
+    }
+    
+    /// USD price and decimals of a token
+    fn token_price(&self, token: Address) -> Result<(U256, u8), Vec<u8>> {
+        let price = pool_stats::fetch_usd_price(
+            self.price_oracle.get(),
+            token,
+            self.max_price_age.get(),
+            U256::from(block::timestamp())
+        )?;
+        
+        Ok((price, tokens::token_decimals(token)?))
+    }
+    
+    /// Stats from oracle prices, the pool's adapter and reported swap volume
+    /// TVL values the real balances of every token the pool holds; fails if any of them has no fresh price
+    fn compute_pool_stats(&self, pool_id: U256, pool: &PoolInfo) -> Result<PoolStats, Vec<u8>> {
+        let adapter = pool_adapter::adapter_for(pool.pool_type).map_err(|e| e.as_bytes().to_vec())?;
+        let state = self.load_state(pool_id);
+        let view = pool.view(&state);
+        
+        let mut tvl_usd = U256::ZERO;
+        for (token, balance) in adapter.holdings(&view)? {
+            let (price, decimals) = self.token_price(token)?;
+            tvl_usd = tvl_usd.saturating_add(pool_stats::usd_value(balance, price, decimals));
+        }
+        
+        // Sell $1k of token_a; pools that cannot absorb it report 100% impact
+        let (price_a, decimals_a) = self.token_price(pool.token_a)?;
+        let probe = pool_stats::amount_for_usd(U256::from(IMPACT_PROBE_USD), price_a, decimals_a);
+        
+        let price_impact_1k = match (adapter.quote_exact_in(&view, true, probe), adapter.spot_price(&view, true)) {
+            (Ok(amount_out), Ok(spot_price)) => pool_stats::price_impact_bps(probe, amount_out, spot_price, pool.fee_bps),
+            _ => U256::from(10000),
         };
         
-        // Utilization rate (how much liquidity is actively used)
-        let utilization_rate = U256::from(20 + (block::number() % 60)); // 20-80%
+        let (volume_24h_usd, fees_24h_usd) = pool_stats::rolling_volume(
+            &self.volume_windows.get(pool_id),
+            U256::from(block::timestamp())
+        );
         
-        PoolStats {
+        Ok(PoolStats {
             tvl_usd,
             volume_24h_usd,
             fees_24h_usd,
             price_impact_1k,
-            utilization_rate,
-        }
+            utilization_rate: pool_stats::utilization_bps(volume_24h_usd, tvl_usd),
+        })
     }
 }
 
@@ -392,25 +763,256 @@ impl PoolRegistry {
 impl PoolRegistry {
     /// Add authorized updater (owner only)
     pub fn add_updater(&mut self, updater: Address) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
         if msg::sender() != self.owner.get() {
             return Err(b"Only owner".to_vec());
         }
         
         self.authorized_updaters.setter(updater).set(true);
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Take a pool out of routing (authorized updater or emergency admin)
+    pub fn deactivate_pool(&mut self, pool_id: U256, reason: String) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let sender = msg::sender();
+        if !self.is_authorized_updater(sender) && sender != self.emergency_admin.get() {
+            return Err(b"Unauthorized".to_vec());
+        }
+        
+        let pool = self.pools.get(pool_id);
+        if pool.pool_address == Address::ZERO {
+            return Err(b"Pool not found".to_vec());
+        }
+        
+        if !pool.is_active {
+            return Err(b"Pool already inactive".to_vec());
+        }
+        
+        if reason.is_empty() {
+            return Err(b"Reason required".to_vec());
+        }
+        
+        self.set_pool_inactive(pool_id, &reason);
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Return a deactivated pool to routing (owner only)
+    pub fn reactivate_pool(&mut self, pool_id: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        let mut pool = self.pools.get(pool_id);
+        if pool.pool_address == Address::ZERO {
+            return Err(b"Pool not found".to_vec());
+        }
+        
+        if pool.is_active {
+            return Err(b"Pool already active".to_vec());
+        }
+        
+        // Only the registration address_to_pool points at may be active
+        if self.find_pool_by_address(pool.pool_address) != Some(pool_id) {
+            return Err(b"Duplicate pool address".to_vec());
+        }
+        
+        pool.is_active = true;
+        self.pools.setter(pool_id).set(pool);
+        
+        if pool_id < self.active_count_cursor.get() {
+            self.count_active_change(pool.chain_id, pool.pool_type, true);
+        }
+        
+        evm::log(PoolReactivated { pool_id });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Delete a pool (owner only); ids are never reused
+    /// The pool is swap-removed from its pair and chain lists (see pool_index::swap_remove)
+    pub fn remove_pool(&mut self, pool_id: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        let pool = self.pools.get(pool_id);
+        if pool.pool_address == Address::ZERO {
+            return Err(b"Pool not found".to_vec());
+        }
+        
+        self.set_pool_inactive(pool_id, "Pool removed");
+        
+        // A duplicate registration may own the address entry
+        if self.address_to_pool.get(pool.pool_address) == pool_id {
+            self.address_to_pool.delete(pool.pool_address);
+        }
+        
+        Self::unlist(&mut self.pair_to_pools.setter(TokenPair::new(pool.token_a, pool.token_b)), pool_id);
+        Self::unlist(&mut self.chain_pools.setter(pool.chain_id), pool_id);
+        
+        self.pools.delete(pool_id);
+        self.pool_stats.delete(pool_id);
+        self.pool_states.delete(pool_id);
+        self.volume_windows.delete(pool_id);
+        
+        evm::log(PoolRemoved {
+            pool_id,
+            pool_address: pool.pool_address,
+        });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Record a swap executed against a pool (authorized updaters only)
+    /// Nothing onchain reports swaps: volume comes solely from offchain updaters watching pool events.
+    /// Volume is valued at the oracle price of `token_in`; fees use the pool's fee rate
+    pub fn report_swap(&mut self, pool_id: U256, token_in: Address, amount_in: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if !self.is_authorized_updater(msg::sender()) {
+            return Err(b"Unauthorized".to_vec());
+        }
+        
+        let pool = self.pools.get(pool_id);
+        if pool.pool_address == Address::ZERO {
+            return Err(b"Pool not found".to_vec());
+        }
+        
+        if token_in != pool.token_a && token_in != pool.token_b {
+            return Err(b"Token not in pool".to_vec());
+        }
+        
+        if amount_in == U256::ZERO {
+            return Err(b"Zero amount".to_vec());
+        }
+        
+        let (price, decimals) = self.token_price(token_in)?;
+        let volume_usd = pool_stats::usd_value(amount_in, price, decimals);
+        let fees_usd = volume_usd * pool.fee_bps / U256::from(10000);
+        
+        let window = pool_stats::record_volume(
+            &self.volume_windows.get(pool_id),
+            U256::from(block::timestamp()),
+            volume_usd,
+            fees_usd
+        );
+        self.volume_windows.setter(pool_id).set(window);
+        
+        evm::log(SwapReported {
+            pool_id,
+            token_in,
+            amount_in,
+            volume_usd,
+        });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Set the USD price oracle used for stats (owner only)
+    pub fn set_price_oracle(&mut self, oracle: Address, max_price_age: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        if oracle == Address::ZERO || max_price_age == U256::ZERO {
+            return Err(b"Invalid oracle config".to_vec());
+        }
+        
+        self.price_oracle.set(oracle);
+        self.max_price_age.set(max_price_age);
+        
+        evm::log(PriceOracleUpdated {
+            oracle,
+            max_price_age,
+        });
+        
+        self.exit_non_reentrant();
         Ok(())
     }
     
     /// Emergency pause (owner or emergency admin)
     pub fn pause(&mut self) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
         let sender = msg::sender();
         if sender != self.owner.get() && sender != self.emergency_admin.get() {
             return Err(b"Unauthorized".to_vec());
         }
         
         self.paused.set(true);
+        self.exit_non_reentrant();
         Ok(())
     }
     
+    /// One-off migration for pools registered before address lookups accepted pool 0
+    /// Pool 0's address could be registered again under a new id; address_to_pool points at
+    /// the newest registration, so every other pool with that address is deactivated
+    /// Processes ids [start_id, start_id + count) and returns how many pools were deactivated
+    pub fn deactivate_duplicate_pools(&mut self, start_id: U256, count: U256) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        let end_id = start_id.saturating_add(count).min(self.pool_count.get());
+        let mut deactivated = U256::ZERO;
+        let mut pool_id = start_id;
+        
+        while pool_id < end_id {
+            let pool = self.pools.get(pool_id);
+            if pool.is_active && self.address_to_pool.get(pool.pool_address) != pool_id {
+                self.set_pool_inactive(pool_id, "Duplicate pool address");
+                deactivated += U256::from(1);
+            }
+            pool_id += U256::from(1);
+        }
+        
+        self.exit_non_reentrant();
+        Ok(deactivated)
+    }
+    
+    /// Backfill active counts for pools registered before counts were tracked
+    /// Counts up to `count` pools from the cursor and returns the new cursor; done once it equals get_pool_count()
+    pub fn sync_active_pool_counts(&mut self, count: U256) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if !self.is_authorized_updater(msg::sender()) {
+            return Err(b"Unauthorized".to_vec());
+        }
+        
+        let mut pool_id = self.active_count_cursor.get();
+        let end_id = pool_id.saturating_add(count).min(self.pool_count.get());
+        
+        while pool_id < end_id {
+            let pool = self.pools.get(pool_id);
+            if pool.is_active {
+                self.count_active_change(pool.chain_id, pool.pool_type, true);
+            }
+            pool_id += U256::from(1);
+        }
+        
+        self.active_count_cursor.set(pool_id);
+        
+        self.exit_non_reentrant();
+        Ok(pool_id)
+    }
+    
     /// Update registry configuration
     pub fn update_config(
         &mut self,
@@ -418,6 +1020,8 @@ impl PoolRegistry {
         max_impact: U256,
         update_freq: U256
     ) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
         if msg::sender() != self.owner.get() {
             return Err(b"Only owner".to_vec());
         }
@@ -432,6 +1036,7 @@ impl PoolRegistry {
             update_frequency: update_freq,
         });
         
+        self.exit_non_reentrant();
         Ok(())
     }
 }
@@ -440,8 +1045,8 @@ impl PoolRegistry {
 POST-HACKATHON SCALING PLAN:
 
 1. REAL DATA INTEGRATION:
-   - Replace simulated reserves with actual pool contract calls
-   - Integrate Chainlink price feeds for USD valuations
+   - Report swap volume from the router on every execution
+   - Fall back to a secondary price oracle when the primary is stale
    - Add support for all major DEX protocols (Uniswap V2/V3, Curve, Balancer, etc.)
 
 2. ADVANCED INDEXING:
@@ -0,0 +1,220 @@
+// AquaFlow Pool Stats - USD valuation from an onchain price oracle and rolling 24h swap volume
+// TVL and price impact are recomputed on pool updates; volume comes from swaps reported by offchain updaters
+
+use stylus_sdk::{
+    alloy_primitives::{Address, U256},
+    prelude::*,
+    call::Call,
+};
+
+/// Oracle prices are USD per whole token with 18 decimals
+pub const USD_PRICE_PRECISION: u64 = 1_000_000_000_000_000_000;
+
+/// Length of the rolling volume window
+pub const VOLUME_WINDOW_SECONDS: u64 = 86400;
+
+/// Swap size used for PoolStats.price_impact_1k, in whole USD
+pub const IMPACT_PROBE_USD: u64 = 1000;
+
+const BPS_DENOMINATOR: u64 = 10000;
+
+sol_interface! {
+    interface IPriceOracle {
+        function getPrice(address token) external view returns (uint256 price, uint256 updatedAt);
+    }
+}
+
+/// Swap volume of the current and previous 24h windows
+/// The rolling total weights the previous window by how much of it still overlaps the last 24h
+#[derive(SolidityType, Clone, Debug, PartialEq, Eq)]
+pub struct VolumeWindow {
+    pub window_start: U256,      // Timestamp the current window opened
+    pub current_usd: U256,
+    pub current_fees_usd: U256,
+    pub previous_usd: U256,
+    pub previous_fees_usd: U256,
+}
+
+/// Reject missing and stale oracle answers
+pub fn check_price(price: U256, updated_at: U256, max_age: U256, now: U256) -> Result<U256, &'static str> {
+    if price == U256::ZERO {
+        return Err("Price unavailable");
+    }
+
+    if updated_at > now || now - updated_at > max_age {
+        return Err("Stale price");
+    }
+
+    Ok(price)
+}
+
+/// Value of a raw token amount in whole USD
+/// Whole tokens and the fractional remainder are priced separately so large balances saturate instead of wrapping
+pub fn usd_value(amount: U256, price: U256, decimals: u8) -> U256 {
+    let unit = U256::from(10).pow(U256::from(decimals));
+    let whole = (amount / unit).saturating_mul(price);
+    let fraction = (amount % unit).saturating_mul(price) / unit;
+    whole.saturating_add(fraction) / U256::from(USD_PRICE_PRECISION)
+}
+
+/// Raw token amount worth `usd` whole dollars
+pub fn amount_for_usd(usd: U256, price: U256, decimals: u8) -> U256 {
+    usd * U256::from(10).pow(U256::from(decimals)) * U256::from(USD_PRICE_PRECISION) / price
+}
+
+/// Price impact in bps: shortfall of `amount_out` against `amount_in` at the spot price, net of the pool fee
+/// `spot_price` has 18 decimals (token_out raw units per token_in raw unit)
+pub fn price_impact_bps(amount_in: U256, amount_out: U256, spot_price: U256, fee_bps: U256) -> U256 {
+    let after_fee = amount_in * (U256::from(BPS_DENOMINATOR) - fee_bps) / U256::from(BPS_DENOMINATOR);
+    let ideal = after_fee * spot_price / U256::from(USD_PRICE_PRECISION);
+    if ideal == U256::ZERO || amount_out >= ideal {
+        return U256::ZERO;
+    }
+
+    (ideal - amount_out) * U256::from(BPS_DENOMINATOR) / ideal
+}
+
+/// 24h volume over TVL in bps; can exceed 10000 when liquidity turns over more than once a day
+pub fn utilization_bps(volume_usd: U256, tvl_usd: U256) -> U256 {
+    if tvl_usd == U256::ZERO {
+        return U256::ZERO;
+    }
+    volume_usd * U256::from(BPS_DENOMINATOR) / tvl_usd
+}
+
+/// Advance the window so that it contains `now`
+fn roll_window(window: &VolumeWindow, now: U256) -> VolumeWindow {
+    let period = U256::from(VOLUME_WINDOW_SECONDS);
+    let mut rolled = window.clone();
+
+    if now < window.window_start + period {
+        return rolled;
+    }
+
+    if now < window.window_start + period * U256::from(2) {
+        // Exactly one window elapsed: current becomes previous
+        rolled.previous_usd = window.current_usd;
+        rolled.previous_fees_usd = window.current_fees_usd;
+        rolled.window_start = window.window_start + period;
+    } else {
+        rolled.previous_usd = U256::ZERO;
+        rolled.previous_fees_usd = U256::ZERO;
+        rolled.window_start = now - (now - window.window_start) % period;
+    }
+
+    rolled.current_usd = U256::ZERO;
+    rolled.current_fees_usd = U256::ZERO;
+    rolled
+}
+
+/// Add a swap to the window
+pub fn record_volume(window: &VolumeWindow, now: U256, volume_usd: U256, fees_usd: U256) -> VolumeWindow {
+    let mut rolled = roll_window(window, now);
+    rolled.current_usd += volume_usd;
+    rolled.current_fees_usd += fees_usd;
+    rolled
+}
+
+/// (volume, fees) over the 24h ending at `now`
+pub fn rolling_volume(window: &VolumeWindow, now: U256) -> (U256, U256) {
+    let rolled = roll_window(window, now);
+    let period = U256::from(VOLUME_WINDOW_SECONDS);
+    let overlap = period - (now - rolled.window_start);
+
+    (
+        rolled.current_usd + rolled.previous_usd * overlap / period,
+        rolled.current_fees_usd + rolled.previous_fees_usd * overlap / period,
+    )
+}
+
+/// USD price of a token from the oracle
+pub fn fetch_usd_price(oracle: Address, token: Address, max_age: U256, now: U256) -> Result<U256, Vec<u8>> {
+    if oracle == Address::ZERO {
+        return Err(b"Price oracle not set".to_vec());
+    }
+
+    let (price, updated_at) = IPriceOracle::new(oracle)
+        .get_price(Call::new(), token)
+        .map_err(|_| b"Price unavailable".to_vec())?;
+
+    check_price(price, updated_at, max_age, now).map_err(|e| e.as_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usd(dollars: u64) -> U256 {
+        U256::from(dollars) * U256::from(USD_PRICE_PRECISION)
+    }
+
+    #[test]
+    fn test_check_price() {
+        let now = U256::from(10_000);
+        let max_age = U256::from(3600);
+
+        assert_eq!(check_price(usd(2), U256::from(9_000), max_age, now), Ok(usd(2)));
+        assert_eq!(check_price(U256::ZERO, now, max_age, now), Err("Price unavailable"));
+        assert_eq!(check_price(usd(2), U256::from(6_000), max_age, now), Err("Stale price"));
+        assert_eq!(check_price(usd(2), U256::from(10_001), max_age, now), Err("Stale price"));
+    }
+
+    #[test]
+    fn test_usd_value_respects_decimals() {
+        // 2,500 USDC (6 decimals) at $1 and 1.5 WETH (18 decimals) at $3,000
+        assert_eq!(usd_value(U256::from(2_500_000_000u64), usd(1), 6), U256::from(2500));
+        let weth = U256::from(1_500_000_000_000_000_000u64);
+        assert_eq!(usd_value(weth, usd(3000), 18), U256::from(4500));
+
+        // $1,000 of WETH and back
+        let amount = amount_for_usd(U256::from(1000), usd(3000), 18);
+        assert_eq!(usd_value(amount, usd(3000), 18), U256::from(999));
+        assert_eq!(amount_for_usd(U256::from(1000), usd(1), 6), U256::from(1_000_000_000u64));
+
+        // Balances whose product with the price exceeds 256 bits saturate
+        assert_eq!(usd_value(U256::MAX, usd(2), 18), U256::MAX / U256::from(USD_PRICE_PRECISION));
+    }
+
+    #[test]
+    fn test_price_impact_bps() {
+        let one = U256::from(USD_PRICE_PRECISION);
+
+        // Fee alone is not impact
+        assert_eq!(price_impact_bps(U256::from(10_000), U256::from(9_970), one, U256::from(30)), U256::ZERO);
+
+        // 1% short of the fee-adjusted spot output
+        let impact = price_impact_bps(U256::from(10_000), U256::from(9_870), one, U256::from(30));
+        assert_eq!(impact, U256::from(100));
+
+        assert_eq!(utilization_bps(U256::from(500), U256::from(1000)), U256::from(5000));
+        assert_eq!(utilization_bps(U256::from(500), U256::ZERO), U256::ZERO);
+    }
+
+    #[test]
+    fn test_rolling_volume_window() {
+        let day = VOLUME_WINDOW_SECONDS;
+        let start = U256::from(day * 100);
+        let empty = VolumeWindow {
+            window_start: start,
+            current_usd: U256::ZERO,
+            current_fees_usd: U256::ZERO,
+            previous_usd: U256::ZERO,
+            previous_fees_usd: U256::ZERO,
+        };
+
+        let window = record_volume(&empty, start + U256::from(100), U256::from(1000), U256::from(3));
+        let window = record_volume(&window, start + U256::from(200), U256::from(500), U256::from(1));
+        assert_eq!(rolling_volume(&window, start + U256::from(300)), (U256::from(1500), U256::from(4)));
+
+        // A quarter into the next day, three quarters of yesterday still counts
+        let later = start + U256::from(day + day / 4);
+        assert_eq!(rolling_volume(&window, later), (U256::from(1125), U256::from(3)));
+
+        let window = record_volume(&window, later, U256::from(100), U256::ZERO);
+        assert_eq!(window.window_start, start + U256::from(day));
+        assert_eq!(rolling_volume(&window, later).0, U256::from(1225));
+
+        // Two idle days clear everything
+        assert_eq!(rolling_volume(&window, later + U256::from(2 * day)), (U256::ZERO, U256::ZERO));
+    }
+}
@@ -5,13 +5,40 @@
 extern crate alloc;
 
 use stylus_sdk::{
-    alloy_primitives::{Address, U256},
+    abi::Bytes,
+    alloy_primitives::{Address, B256, I256, U256},
     prelude::*,
     storage::{StorageMap, StorageU256, StorageVec, StorageAddress, StorageBool},
-    call::Call,
+    call::{Call, RawCall},
     evm,
+    contract,
 };
 
+use crate::alerts::{self, AlertCode, AlertDetails, NO_POOL};
+use crate::batch_auction::{
+    self, Batch, BatchOrder, Clearing, ClearingOrder, BATCH_ORDER_FILLED, BATCH_ORDER_PENDING, BATCH_ORDER_REFUNDED,
+    MAX_BATCH_ORDERS, MAX_BATCH_WINDOW,
+};
+use crate::commit_reveal::{self, IntentCommitment};
+use crate::dca::{self, DcaSchedule, MIN_DCA_INTERVAL};
+use crate::dynamic_fees::{self, DynamicFeeConfig, VolatilityState};
+use crate::fees;
+use crate::gas_meter::{self, GasMeter, GasStage, TX_BASE_GAS};
+use crate::guard::NonReentrant;
+use crate::hooks::{self, HookConfig, HookSwap, HOOK_AFTER_SWAP, HOOK_BEFORE_SWAP};
+use crate::limit_orders::{self, LimitOrder, MAX_KEEPER_BOUNTY_BPS};
+use crate::nonces;
+use crate::pool_adapter::{self, PoolAdapter, PoolSnapshot, PoolView, SwapParams, SwapPayment};
+use crate::pool_index;
+use crate::pool_stats;
+use crate::signatures::{self, Signature, PERMIT2_WITNESS_TYPE};
+use crate::solver_auction::{
+    self, Solution, Solver, SolverAuction, MAX_SOLUTIONS_PER_AUCTION, MAX_SOLUTION_HOPS,
+    MAX_SOLVER_SLASH_BPS, MAX_SOLVER_SURPLUS_SHARE_BPS, MAX_SOLVER_WINDOW, SOLVER_SETTLEMENT_WINDOW,
+    SOLVER_UNBONDING_PERIOD,
+};
+use crate::tokens;
+
 // Security constants
 const MAX_SLIPPAGE_BPS: u32 = 1000; // 10% max slippage
 const MAX_FEE_BPS: u32 = 1000; // 10% max pool fee
@@ -31,10 +58,11 @@ pub struct SecurePool {
     pub is_verified: bool,
     pub created_at: U256,
     pub last_updated: U256,
+    pub pool_type: u8, // Adapter serving the pool, see pool_adapter::adapter_for
 }
 
 // Validated intent structure
-#[derive(SolidityType)]
+#[derive(SolidityType, Clone)]
 pub struct ValidatedIntent {
     pub user: Address,
     pub token_in: Address,
@@ -44,6 +72,40 @@ pub struct ValidatedIntent {
     pub deadline: U256,
     pub max_slippage_bps: U256,
     pub nonce: U256,
+    pub unordered_nonce: bool, // true = Permit2-style bitmap nonce, false = sequential
+    pub recipient: Address,    // Receives token_out (zero = user)
+    pub referrer: Address,     // Front-end credited with a share of the protocol fee (zero = none)
+    pub referral_fee_bps: U256, // Referrer share of amount_out, capped by max_referral_fee_bps
+}
+
+impl ValidatedIntent {
+    /// ABI-encoded words of the intent, used to price its calldata
+    pub fn abi_words(&self) -> Vec<U256> {
+        let address_word = |address: Address| U256::from_be_slice(address.as_slice());
+        vec![
+            address_word(self.user),
+            address_word(self.token_in),
+            address_word(self.token_out),
+            self.amount_in,
+            self.min_amount_out,
+            self.deadline,
+            self.max_slippage_bps,
+            self.nonce,
+            U256::from(self.unordered_nonce as u8),
+            address_word(self.recipient),
+            address_word(self.referrer),
+            self.referral_fee_bps,
+        ]
+    }
+    
+    /// Address that receives the swap output
+    pub fn output_recipient(&self) -> Address {
+        if self.recipient == Address::ZERO {
+            self.user
+        } else {
+            self.recipient
+        }
+    }
 }
 
 // Security-focused route step
@@ -55,9 +117,54 @@ pub struct SecureRouteStep {
     pub amount_in: U256,
     pub amount_out: U256,
     pub price_impact_bps: U256,
+    pub fee_bps: U256, // Effective pool fee applied to this hop
     pub verified: bool,
 }
 
+// How token_in is pulled from the user during intent execution
+enum Funding {
+    /// transferFrom against an existing allowance (approve or EIP-2612 permit)
+    Allowance,
+    /// Permit2 signature transfer with the intent as witness
+    Permit2 {
+        nonce: U256,
+        deadline: U256,
+        signature: Vec<u8>,
+    },
+}
+
+// Router pool with its state freshly read through its adapter
+struct LivePool {
+    pool: SecurePool,
+    adapter: &'static dyn PoolAdapter,
+    snapshot: PoolSnapshot,
+}
+
+impl LivePool {
+    /// Adapter view at the pool's own fee
+    fn view(&self) -> PoolView<'_> {
+        PoolView {
+            pool_address: self.pool.pool_address,
+            token_a: self.pool.token_a,
+            token_b: self.pool.token_b,
+            reserve_a: self.snapshot.reserve_a,
+            reserve_b: self.snapshot.reserve_b,
+            fee_bps: self.pool.fee_bps,
+            state: &self.snapshot.state,
+        }
+    }
+}
+
+// Queued emergency withdrawal awaiting its delay
+#[derive(SolidityType, Clone)]
+pub struct EmergencyWithdrawal {
+    pub token: Address,
+    pub recipient: Address,
+    pub amount: U256,
+    pub executable_at: U256, // Timestamp after which it can be executed
+    pub is_pending: bool,
+}
+
 sol_storage! {
     #[entrypoint]
     pub struct SecureAquaFlowRouter {
@@ -95,6 +202,88 @@ sol_storage! {
         circuit_breaker_threshold: StorageU256,
         total_volume_24h: StorageU256,
         emergency_withdrawal_delay: StorageU256,
+        
+        // Limit orders
+        limit_orders: StorageMap<U256, LimitOrder>,
+        limit_order_count: StorageU256,
+        keeper_bounty_bps: StorageU256,
+        
+        // DCA schedules
+        dca_schedules: StorageMap<U256, DcaSchedule>,
+        dca_schedule_count: StorageU256,
+        
+        // Referrals
+        max_referral_fee_bps: StorageU256,
+        referral_earnings: StorageMap<(Address, Address), U256>, // (referrer, token) -> claimable
+        referral_volume: StorageMap<Address, U256>,  // referrer -> attributed amount_in
+        referral_count: StorageMap<Address, U256>,   // referrer -> referred intents
+        
+        // Batch auctions
+        batches: StorageMap<U256, Batch>,
+        batch_count: StorageU256,
+        batch_window: StorageU256,
+        open_batches: StorageMap<(Address, Address), U256>, // (token0, token1) -> latest batch
+        batch_order_ids: StorageMap<U256, StorageVec<U256>>, // batch -> order ids
+        batch_orders: StorageMap<U256, BatchOrder>,
+        batch_order_count: StorageU256,
+        
+        // Solver competition
+        solvers: StorageMap<Address, Solver>,
+        solver_bond_token: StorageAddress,
+        min_solver_bond: StorageU256,
+        solver_window: StorageU256,
+        solver_surplus_share_bps: StorageU256,
+        solver_slash_bps: StorageU256,
+        solver_auctions: StorageMap<U256, SolverAuction>,
+        solver_auction_count: StorageU256,
+        auction_solution_ids: StorageMap<U256, StorageVec<U256>>, // auction -> solution ids
+        solutions: StorageMap<U256, Solution>,
+        solution_routes: StorageMap<U256, StorageVec<U256>>,      // solution -> pool ids
+        solution_count: StorageU256,
+        solver_submitted: StorageMap<(U256, Address), bool>,      // (auction, solver)
+        
+        // Unordered nonces
+        nonce_bitmaps: StorageMap<(Address, U256), U256>, // (user, word_pos) -> used bits
+        
+        // Protocol fee accrual
+        protocol_fees: StorageMap<Address, U256>, // token -> accrued protocol fees
+        
+        // Reentrancy guard
+        reentrancy_locked: StorageBool,
+        
+        // Emergency withdrawals
+        emergency_withdrawals: StorageMap<U256, EmergencyWithdrawal>,
+        emergency_withdrawal_count: StorageU256,
+        
+        // Compliance lists
+        guardians: StorageMap<Address, bool>,
+        denied_users: StorageMap<Address, bool>,
+        denied_tokens: StorageMap<Address, bool>,
+        allowed_tokens: StorageMap<Address, bool>,
+        token_allowlist_mode: StorageBool, // When set, only allowed tokens can be traded or pooled
+        
+        // Gas accounting
+        total_gas_used: StorageU256,
+        metered_executions: StorageU256,
+        gas_debug_enabled: StorageBool,
+        
+        // Swap hooks
+        pool_hooks: StorageMap<U256, HookConfig>,
+        user_hooks: StorageMap<Address, HookConfig>,
+        
+        // Dynamic fees
+        dynamic_fee_configs: StorageMap<U256, DynamicFeeConfig>,
+        pool_volatility: StorageMap<U256, VolatilityState>,
+        
+        // Commit-reveal
+        intent_commitments: StorageMap<(Address, B256), IntentCommitment>, // (committer, commitment)
+        reveal_delay_blocks: StorageU256,
+        reveal_window_blocks: StorageU256,
+        
+        // Swap execution
+        active_swap_pool: StorageAddress, // Pool allowed to call uniswapV3SwapCallback (zero outside a V3 hop)
+        active_swap_token: StorageAddress,
+        active_swap_amount: StorageU256,  // Exact token_in the callback pays
     }
 }
 
@@ -104,17 +293,75 @@ sol! {
         address indexed user,
         address indexed token_in,
         address indexed token_out,
+        address recipient,
         uint256 amount_in,
         uint256 amount_out,
         uint256 price_impact_bps,
+        uint256 total_fee_bps, // Sum of the effective pool fees over every hop
         uint256 gas_used
     );
     
+    event IntentCommitted(
+        bytes32 indexed commitment,
+        address indexed user,
+        uint256 commit_block
+    );
+    
+    event IntentRevealed(
+        bytes32 indexed commitment,
+        address indexed user,
+        address indexed revealer
+    );
+    
+    event CommitmentCancelled(
+        bytes32 indexed commitment,
+        address indexed user
+    );
+    
+    event DynamicFeeConfigured(
+        uint256 indexed pool_id,
+        bool enabled,
+        uint256 sensitivity_bps,
+        uint256 max_fee_bps
+    );
+    
+    event PoolHookUpdated(
+        uint256 indexed pool_id,
+        address indexed hook,
+        uint8 permissions,
+        uint256 gas_limit
+    );
+    
+    event UserHookUpdated(
+        address indexed user,
+        address indexed hook,
+        uint8 permissions,
+        uint256 gas_limit
+    );
+    
+    event HookFailed(
+        address indexed hook,
+        address indexed user,
+        uint256 indexed pool_id,
+        uint8 stage
+    );
+    
+    // Debug-only breakdown; stage_gas is ordered as gas_meter::GasStage
+    event GasBreakdown(
+        address indexed user,
+        uint256[] stage_gas,
+        uint256[] hop_gas,
+        uint256 event_gas,
+        uint256 intrinsic_gas,
+        uint256 total_gas
+    );
+    
     event SecurityAlert(
         address indexed user,
+        uint8 indexed alert_code,
         string alert_type,
         uint256 severity,
-        bytes32 details
+        bytes details
     );
     
     event PoolVerified(
@@ -128,6 +375,219 @@ sol! {
         string action_type,
         uint256 timestamp
     );
+    
+    event EmergencyWithdrawalQueued(
+        uint256 indexed withdrawal_id,
+        address indexed token,
+        address recipient,
+        uint256 amount,
+        uint256 executable_at
+    );
+    
+    event EmergencyWithdrawalExecuted(
+        uint256 indexed withdrawal_id,
+        address indexed token,
+        address recipient,
+        uint256 amount
+    );
+    
+    event EmergencyWithdrawalCancelled(
+        uint256 indexed withdrawal_id,
+        address indexed canceller
+    );
+    
+    event GuardianUpdated(
+        address indexed guardian,
+        bool enabled
+    );
+    
+    event UserDenyListUpdated(
+        address indexed user,
+        address indexed updated_by,
+        bool denied
+    );
+    
+    event TokenDenyListUpdated(
+        address indexed token,
+        address indexed updated_by,
+        bool denied
+    );
+    
+    event TokenAllowListUpdated(
+        address indexed token,
+        address indexed updated_by,
+        bool allowed
+    );
+    
+    event TokenAllowlistModeUpdated(
+        bool enabled
+    );
+    
+    event NonceCancelled(
+        address indexed user,
+        uint256 nonce
+    );
+    
+    event NoncesInvalidated(
+        address indexed user,
+        uint256 new_nonce
+    );
+    
+    event UnorderedNoncesInvalidated(
+        address indexed user,
+        uint256 word_pos,
+        uint256 mask
+    );
+    
+    event LimitOrderPlaced(
+        uint256 indexed order_id,
+        address indexed user,
+        address token_in,
+        address token_out,
+        uint256 amount_in,
+        uint256 min_amount_out,
+        uint256 deadline
+    );
+    
+    event LimitOrderFilled(
+        uint256 indexed order_id,
+        address indexed keeper,
+        uint256 amount_in,
+        uint256 amount_out,
+        uint256 bounty
+    );
+    
+    event LimitOrderCancelled(
+        uint256 indexed order_id,
+        uint256 refunded
+    );
+    
+    event DcaScheduleCreated(
+        uint256 indexed schedule_id,
+        address indexed user,
+        address token_in,
+        address token_out,
+        uint256 budget,
+        uint256 amount_per_period,
+        uint256 interval
+    );
+    
+    event DcaExecuted(
+        uint256 indexed schedule_id,
+        address indexed keeper,
+        uint256 amount_in,
+        uint256 amount_out,
+        uint256 next_execution
+    );
+    
+    event DcaScheduleCancelled(
+        uint256 indexed schedule_id,
+        uint256 refunded
+    );
+    
+    event ReferralCredited(
+        address indexed referrer,
+        address indexed user,
+        address indexed token,
+        uint256 amount
+    );
+    
+    event ReferralFeesClaimed(
+        address indexed referrer,
+        address indexed token,
+        uint256 amount
+    );
+    
+    event ProtocolFeesClaimed(
+        address indexed recipient,
+        address indexed token,
+        uint256 amount
+    );
+    
+    event BatchOpened(
+        uint256 indexed batch_id,
+        address indexed token0,
+        address indexed token1,
+        uint256 end_time
+    );
+    
+    event BatchIntentSubmitted(
+        uint256 indexed order_id,
+        uint256 indexed batch_id,
+        address indexed user,
+        address token_in,
+        uint256 amount_in,
+        uint256 min_amount_out
+    );
+    
+    event BatchIntentCancelled(
+        uint256 indexed order_id,
+        uint256 refunded
+    );
+    
+    event BatchOrderSettled(
+        uint256 indexed order_id,
+        uint256 indexed batch_id,
+        bool filled,
+        uint256 amount_out
+    );
+    
+    event BatchSettled(
+        uint256 indexed batch_id,
+        uint256 clearing_price,
+        uint256 matched_token0,
+        uint256 matched_token1,
+        uint256 pool_amount_in,
+        uint256 pool_amount_out,
+        uint256 filled_count,
+        uint256 refunded_count
+    );
+    
+    event SolverBonded(
+        address indexed solver,
+        uint256 amount,
+        uint256 total_bond
+    );
+    
+    event SolverExitRequested(
+        address indexed solver,
+        uint256 unlock_time
+    );
+    
+    event SolverBondWithdrawn(
+        address indexed solver,
+        uint256 amount
+    );
+    
+    event SolverSlashed(
+        address indexed solver,
+        uint256 indexed auction_id,
+        uint256 amount
+    );
+    
+    event SolverAuctionOpened(
+        uint256 indexed auction_id,
+        address indexed user,
+        address token_in,
+        address token_out,
+        uint256 amount_in,
+        uint256 min_amount_out,
+        uint256 end_time
+    );
+    
+    event SolutionSubmitted(
+        uint256 indexed auction_id,
+        uint256 indexed solution_id,
+        address indexed solver,
+        uint256 amount_out
+    );
+    
+    event SolverAuctionSettled(
+        uint256 indexed auction_id,
+        address indexed winner,
+        uint256 amount_out,
+        uint256 solver_reward
+    );
 }
 
 #[external]
@@ -139,6 +599,8 @@ impl SecureAquaFlowRouter {
         emergency_admin: Address,
         fee_recipient: Address
     ) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
         // Validate initialization parameters
         if owner == Address::ZERO || emergency_admin == Address::ZERO || fee_recipient == Address::ZERO {
             return Err(b"Invalid initialization parameters".to_vec());
@@ -156,76 +618,103 @@ impl SecureAquaFlowRouter {
         self.circuit_breaker_threshold.set(U256::from(10000000) * U256::from(10).pow(U256::from(18))); // 10M circuit breaker
         self.pool_creation_fee.set(U256::from(10).pow(U256::from(17))); // 0.1 ETH
         self.emergency_withdrawal_delay.set(U256::from(86400)); // 24 hours
+        self.keeper_bounty_bps.set(U256::from(10)); // 0.1% keeper bounty
+        self.max_referral_fee_bps.set(U256::from(10)); // 0.1% max referral share
+        self.reveal_delay_blocks.set(U256::from(2)); // Reveal no earlier than 2 blocks after commit
+        self.reveal_window_blocks.set(U256::from(50)); // Then within 50 blocks
+        self.batch_window.set(U256::from(30)); // 30 second batch auctions
+        self.solver_window.set(U256::from(12)); // 12 seconds for solver solutions
+        self.solver_surplus_share_bps.set(U256::from(1000)); // Solvers keep 10% of surplus
+        self.solver_slash_bps.set(U256::from(5000)); // Failed settlement costs 50% of bond
         
         self.paused.set(false);
         self.last_reset.set(U256::from(block::timestamp()));
         
+        self.exit_non_reentrant();
         Ok(())
     }
     
     /// Execute intent with comprehensive security checks
     pub fn execute_secure_intent(&mut self, intent: ValidatedIntent) -> Result<U256, Vec<u8>> {
-        let gas_start = evm::gas_left();
-        
-        // SECURITY CHECK 1: Validate caller and intent
-        self.validate_secure_intent(&intent)?;
+        self.enter_non_reentrant()?;
         
-        // SECURITY CHECK 2: Anti-abuse measures
-        self.check_abuse_protection(&intent)?;
+        let amount_out = self.execute_intent_pipeline(intent, msg::sender(), Funding::Allowance)?;
         
-        // SECURITY CHECK 3: Economic security
-        self.check_economic_limits(&intent)?;
+        self.exit_non_reentrant();
+        Ok(amount_out)
+    }
+    
+    /// Relayed execution with an EIP-2612 permit for token_in
+    /// `intent_signature` is the user's EIP-712 signature over the intent
+    pub fn execute_intent_with_permit(
+        &mut self,
+        intent: ValidatedIntent,
+        permit_deadline: U256,
+        permit_signature: Signature,
+        intent_signature: Signature
+    ) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
         
-        // SECURITY CHECK 4: Circuit breaker
-        self.check_circuit_breaker(&intent)?;
+        let signer = signatures::recover_signer(self.intent_digest(&intent), &intent_signature)?;
         
-        // Find and validate secure route
-        let route = self.find_secure_route(
+        tokens::try_permit(
+            self,
             intent.token_in,
-            intent.token_out,
+            intent.user,
             intent.amount_in,
-            intent.max_slippage_bps
-        )?;
-        
-        // SECURITY CHECK 5: Route validation
-        self.validate_route_security(&route, &intent)?;
-        
-        // Execute with reentrancy protection
-        let amount_out = self.execute_secure_route(&intent, &route)?;
-        
-        // SECURITY CHECK 6: Output validation
-        if amount_out < intent.min_amount_out {
-            return Err(b"Insufficient output amount".to_vec());
-        }
+            permit_deadline,
+            permit_signature.v,
+            permit_signature.r,
+            permit_signature.s
+        );
         
-        // Update security metrics
-        self.update_security_metrics(&intent, amount_out)?;
+        let amount_out = self.execute_intent_pipeline(intent, signer, Funding::Allowance)?;
         
-        let gas_used = gas_start - evm::gas_left();
-        let price_impact = self.calculate_total_price_impact(&route);
+        self.exit_non_reentrant();
+        Ok(amount_out)
+    }
+    
+    /// Relayed execution funded by a Permit2 signature transfer
+    /// The Permit2 signature covers the intent as witness, so no separate intent signature is needed
+    pub fn execute_intent_with_permit2(
+        &mut self,
+        intent: ValidatedIntent,
+        permit_nonce: U256,
+        permit_deadline: U256,
+        signature: Vec<u8>
+    ) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        // Permit2 verifies the user's signature when the tokens are pulled
+        let user = intent.user;
+        let funding = Funding::Permit2 {
+            nonce: permit_nonce,
+            deadline: permit_deadline,
+            signature,
+        };
         
-        // Emit secure event
-        evm::log(SecureIntentExecuted {
-            user: intent.user,
-            token_in: intent.token_in,
-            token_out: intent.token_out,
-            amount_in: intent.amount_in,
-            amount_out,
-            price_impact_bps: U256::from(price_impact),
-            gas_used,
-        });
+        let amount_out = self.execute_intent_pipeline(intent, user, funding)?;
         
+        self.exit_non_reentrant();
         Ok(amount_out)
     }
     
+    /// EIP-712 digest a user signs to authorize an intent
+    pub fn get_intent_digest(&self, intent: ValidatedIntent) -> B256 {
+        self.intent_digest(&intent)
+    }
+    
     /// Add pool with comprehensive validation
     pub fn add_secure_pool(
         &mut self,
         token_a: Address,
         token_b: Address,
         pool_address: Address,
-        fee_bps: U256
+        fee_bps: U256,
+        pool_type: u8
     ) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
         // Access control
         if !self.is_authorized_caller(msg::sender()) {
             return Err(b"Unauthorized caller".to_vec());
@@ -233,11 +722,12 @@ impl SecureAquaFlowRouter {
         
         // Validate pool parameters
         self.validate_pool_parameters(token_a, token_b, pool_address, fee_bps)?;
+        let adapter = pool_adapter::adapter_for(pool_type).map_err(|e| e.as_bytes().to_vec())?;
         
         let pool_id = self.pool_count.get();
         
         // Get and validate reserves
-        let (reserve_a, reserve_b) = self.get_verified_pool_reserves(pool_address)?;
+        let (reserve_a, reserve_b) = self.get_verified_pool_reserves(adapter, pool_address, token_a, token_b)?;
         
         // Create secure pool
         let secure_pool = SecurePool {
@@ -250,6 +740,7 @@ impl SecureAquaFlowRouter {
             is_verified: false, // Requires separate verification
             created_at: U256::from(block::timestamp()),
             last_updated: U256::from(block::timestamp()),
+            pool_type,
         };
         
         // Store pool
@@ -263,11 +754,14 @@ impl SecureAquaFlowRouter {
         
         self.pool_count.set(pool_id + U256::from(1));
         
+        self.exit_non_reentrant();
         Ok(pool_id)
     }
     
     /// Emergency pause function
     pub fn emergency_pause(&mut self) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
         let caller = msg::sender();
         if caller != self.owner.get() && caller != self.emergency_admin.get() {
             return Err(b"Unauthorized emergency action".to_vec());
@@ -281,11 +775,134 @@ impl SecureAquaFlowRouter {
             timestamp: U256::from(block::timestamp()),
         });
         
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Queue an emergency withdrawal of router-held funds (only while paused)
+    /// Executable once `emergency_withdrawal_delay` has elapsed
+    pub fn queue_emergency_withdrawal(
+        &mut self,
+        token: Address,
+        recipient: Address,
+        amount: U256
+    ) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let caller = msg::sender();
+        if caller != self.owner.get() && caller != self.emergency_admin.get() {
+            return Err(b"Unauthorized emergency action".to_vec());
+        }
+        
+        if !self.paused.get() {
+            return Err(b"Router not paused".to_vec());
+        }
+        
+        if token == Address::ZERO || recipient == Address::ZERO || amount == U256::ZERO {
+            return Err(b"Invalid withdrawal parameters".to_vec());
+        }
+        
+        let withdrawal_id = self.emergency_withdrawal_count.get();
+        let executable_at = U256::from(block::timestamp()) + self.emergency_withdrawal_delay.get();
+        
+        self.emergency_withdrawals.setter(withdrawal_id).set(EmergencyWithdrawal {
+            token,
+            recipient,
+            amount,
+            executable_at,
+            is_pending: true,
+        });
+        self.emergency_withdrawal_count.set(withdrawal_id + U256::from(1));
+        
+        evm::log(EmergencyWithdrawalQueued {
+            withdrawal_id,
+            token,
+            recipient,
+            amount,
+            executable_at,
+        });
+        
+        self.exit_non_reentrant();
+        Ok(withdrawal_id)
+    }
+    
+    /// Execute a queued emergency withdrawal after its delay (only while paused)
+    pub fn execute_emergency_withdrawal(&mut self, withdrawal_id: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let caller = msg::sender();
+        if caller != self.owner.get() && caller != self.emergency_admin.get() {
+            return Err(b"Unauthorized emergency action".to_vec());
+        }
+        
+        if !self.paused.get() {
+            return Err(b"Router not paused".to_vec());
+        }
+        
+        let mut withdrawal = self.emergency_withdrawals.get(withdrawal_id);
+        if !withdrawal.is_pending {
+            return Err(b"Withdrawal not pending".to_vec());
+        }
+        
+        if U256::from(block::timestamp()) < withdrawal.executable_at {
+            return Err(b"Withdrawal delay not elapsed".to_vec());
+        }
+        
+        let token = withdrawal.token;
+        let recipient = withdrawal.recipient;
+        let amount = withdrawal.amount;
+        
+        withdrawal.is_pending = false;
+        self.emergency_withdrawals.setter(withdrawal_id).set(withdrawal);
+        
+        tokens::push_tokens(self, token, recipient, amount)?;
+        
+        evm::log(EmergencyWithdrawalExecuted {
+            withdrawal_id,
+            token,
+            recipient,
+            amount,
+        });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Cancel a queued emergency withdrawal (owner only)
+    pub fn cancel_emergency_withdrawal(&mut self, withdrawal_id: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let caller = msg::sender();
+        if caller != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        let mut withdrawal = self.emergency_withdrawals.get(withdrawal_id);
+        if !withdrawal.is_pending {
+            return Err(b"Withdrawal not pending".to_vec());
+        }
+        
+        withdrawal.is_pending = false;
+        self.emergency_withdrawals.setter(withdrawal_id).set(withdrawal);
+        
+        evm::log(EmergencyWithdrawalCancelled {
+            withdrawal_id,
+            canceller: caller,
+        });
+        
+        self.exit_non_reentrant();
         Ok(())
     }
     
+    /// Get queued emergency withdrawal by ID
+    pub fn get_emergency_withdrawal(&self, withdrawal_id: U256) -> EmergencyWithdrawal {
+        self.emergency_withdrawals.get(withdrawal_id)
+    }
+    
     /// Verify pool (requires validator role)
     pub fn verify_pool(&mut self, pool_id: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
         let caller = msg::sender();
         if !self.pool_validators.get(caller) {
             return Err(b"Not authorized to verify pools".to_vec());
@@ -308,42 +925,247 @@ impl SecureAquaFlowRouter {
             timestamp: U256::from(block::timestamp()),
         });
         
+        self.exit_non_reentrant();
         Ok(())
     }
-}
-
-// Security validation functions
-impl SecureAquaFlowRouter {
-    /// Comprehensive intent validation
-    fn validate_secure_intent(&self, intent: &ValidatedIntent) -> Result<(), Vec<u8>> {
-        // Check if paused
-        if self.paused.get() {
-            return Err(b"Router is paused".to_vec());
-        }
+    
+    /// Cancel a single unordered nonce so any intent signed with it can never execute
+    pub fn cancel_nonce(&mut self, nonce: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
         
-        // Validate user matches caller
-        if intent.user != msg::sender() {
-            return Err(b"Intent user mismatch".to_vec());
-        }
+        let user = msg::sender();
+        let (word_pos, mask) = nonces::bitmap_position(nonce);
         
-        // Validate addresses
-        if intent.token_in == Address::ZERO || intent.token_out == Address::ZERO {
-            return Err(b"Invalid token addresses".to_vec());
+        let bitmap = self.nonce_bitmaps.get((user, word_pos));
+        if nonces::is_bit_set(bitmap, mask) {
+            return Err(b"Nonce already used".to_vec());
         }
         
-        if intent.token_in == intent.token_out {
-            return Err(b"Same token swap".to_vec());
-        }
+        self.nonce_bitmaps.setter((user, word_pos)).set(bitmap | mask);
         
-        // Validate amounts
-        if intent.amount_in == U256::ZERO {
-            return Err(b"Amount must be greater than zero".to_vec());
-        }
+        evm::log(NonceCancelled { user, nonce });
         
-        let min_amount = self.min_trade_amount.get();
-        let max_amount = self.max_trade_amount.get();
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Invalidate every unordered nonce whose bit is set in `mask` for the given word
+    pub fn invalidate_unordered_nonces(&mut self, word_pos: U256, mask: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
         
-        if intent.amount_in < min_amount {
+        let user = msg::sender();
+        let bitmap = self.nonce_bitmaps.get((user, word_pos));
+        self.nonce_bitmaps.setter((user, word_pos)).set(bitmap | mask);
+        
+        evm::log(UnorderedNoncesInvalidated { user, word_pos, mask });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Skip the sequential nonce forward, invalidating every nonce below `new_nonce`
+    pub fn invalidate_nonces_up_to(&mut self, new_nonce: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let user = msg::sender();
+        let current = self.user_nonces.get(user);
+        
+        let new_nonce = nonces::checked_invalidation(current, new_nonce)
+            .map_err(|e| e.as_bytes().to_vec())?;
+        self.user_nonces.setter(user).set(new_nonce);
+        
+        evm::log(NoncesInvalidated { user, new_nonce });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Get the next expected sequential nonce for a user
+    pub fn get_nonce(&self, user: Address) -> U256 {
+        self.user_nonces.get(user)
+    }
+    
+    /// Check whether an unordered nonce has been used or cancelled
+    pub fn is_nonce_used(&self, user: Address, nonce: U256) -> bool {
+        let (word_pos, mask) = nonces::bitmap_position(nonce);
+        nonces::is_bit_set(self.nonce_bitmaps.get((user, word_pos)), mask)
+    }
+}
+
+impl NonReentrant for SecureAquaFlowRouter {
+    fn reentrancy_locked(&self) -> bool {
+        self.reentrancy_locked.get()
+    }
+    
+    fn set_reentrancy_locked(&mut self, locked: bool) {
+        self.reentrancy_locked.set(locked);
+    }
+}
+
+// Security validation functions
+impl SecureAquaFlowRouter {
+    /// Full execution pipeline for an intent authorized by `authorized_by`
+    fn execute_intent_pipeline(
+        &mut self,
+        intent: ValidatedIntent,
+        authorized_by: Address,
+        funding: Funding
+    ) -> Result<U256, Vec<u8>> {
+        let mut meter = GasMeter::new(evm::gas_left());
+        
+        // SECURITY CHECK 1: Validate caller and intent
+        self.validate_secure_intent(&intent, authorized_by)?;
+        meter.record(GasStage::Validation, evm::gas_left());
+        
+        // SECURITY CHECKS 2-4: Anti-abuse, economic security and circuit breaker
+        self.check_security(&intent, true, Some(&mut meter))?;
+        
+        // Find and validate secure route
+        let route = self.find_secure_route(
+            intent.token_in,
+            intent.token_out,
+            intent.amount_in,
+            intent.max_slippage_bps
+        )?;
+        meter.record(GasStage::RouteSearch, evm::gas_left());
+        
+        // SECURITY CHECK 5: Route validation
+        self.validate_route_security(&route, &intent)?;
+        meter.record(GasStage::RouteValidation, evm::gas_left());
+        
+        // Consume nonce before any state change (replay protection)
+        self.consume_nonce(&intent)?;
+        
+        self.pull_intent_funds(&intent, funding)?;
+        meter.record(GasStage::Settlement, evm::gas_left());
+        
+        // Execute with reentrancy protection
+        let gross_amount_out = self.execute_secure_route(intent.user, &route, Some(&mut meter))?;
+        
+        // Charge protocol fee, crediting the referrer's share
+        let amount_out = self.charge_protocol_fee(&intent, gross_amount_out);
+        
+        // SECURITY CHECK 6: Output validation
+        if amount_out < intent.min_amount_out {
+            return Err(b"Insufficient output amount".to_vec());
+        }
+        
+        // Update security metrics
+        self.update_security_metrics(&intent, amount_out)?;
+        
+        let recipient = intent.output_recipient();
+        tokens::push_tokens(self, intent.token_out, recipient, amount_out)?;
+        meter.record(GasStage::Settlement, evm::gas_left());
+        
+        // Add what the VM can't meter: the execution event itself and intrinsic cost
+        // (4 topics, 6 data words in SecureIntentExecuted)
+        let event_gas = gas_meter::log_gas(4, 6 * 32);
+        let intrinsic_gas = TX_BASE_GAS + gas_meter::calldata_gas(&intent.abi_words());
+        let gas_used = meter.execution_gas(evm::gas_left()) + event_gas + intrinsic_gas;
+        
+        self.record_gas_usage(&intent, &meter, event_gas, intrinsic_gas, gas_used);
+        
+        let price_impact = self.calculate_total_price_impact(&route);
+        
+        // Emit secure event
+        evm::log(SecureIntentExecuted {
+            user: intent.user,
+            token_in: intent.token_in,
+            token_out: intent.token_out,
+            recipient,
+            amount_in: intent.amount_in,
+            amount_out,
+            price_impact_bps: U256::from(price_impact),
+            total_fee_bps: route.iter().map(|step| step.fee_bps).sum(),
+            gas_used: U256::from(gas_used),
+        });
+        
+        Ok(amount_out)
+    }
+    
+    /// Pull token_in from the user according to the funding mode
+    fn pull_intent_funds(&mut self, intent: &ValidatedIntent, funding: Funding) -> Result<(), Vec<u8>> {
+        match funding {
+            Funding::Allowance => {
+                tokens::pull_tokens(self, intent.token_in, intent.user, intent.amount_in)
+            },
+            Funding::Permit2 { nonce, deadline, signature } => {
+                let witness = signatures::intent_struct_hash(&intent.abi_words());
+                tokens::pull_with_permit2(
+                    self,
+                    intent.token_in,
+                    intent.user,
+                    intent.amount_in,
+                    nonce,
+                    deadline,
+                    witness,
+                    PERMIT2_WITNESS_TYPE,
+                    signature
+                )
+            },
+        }
+    }
+    
+    /// EIP-712 digest of an intent under this router's domain
+    fn intent_digest(&self, intent: &ValidatedIntent) -> B256 {
+        let domain = signatures::domain_separator(block::chainid(), contract::address());
+        signatures::typed_data_digest(domain, signatures::intent_struct_hash(&intent.abi_words()))
+    }
+    
+    /// Comprehensive intent validation
+    /// `authorized_by` is the caller, or the signer for relayed intents
+    fn validate_secure_intent(&self, intent: &ValidatedIntent, authorized_by: Address) -> Result<(), Vec<u8>> {
+        // Check if paused
+        if self.paused.get() {
+            return Err(b"Router is paused".to_vec());
+        }
+        
+        // Validate user matches caller or signer
+        if intent.user != authorized_by {
+            return Err(b"Intent user mismatch".to_vec());
+        }
+        
+        self.validate_intent_terms(intent)?;
+        
+        // Validate nonce
+        if intent.unordered_nonce {
+            if self.is_nonce_used(intent.user, intent.nonce) {
+                return Err(b"Nonce already used".to_vec());
+            }
+        } else {
+            let expected_nonce = self.user_nonces.get(intent.user);
+            if intent.nonce != expected_nonce {
+                return Err(b"Invalid nonce".to_vec());
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Validate tokens, amounts, deadline and slippage of an intent
+    /// Shared by user-submitted intents and router-generated ones (DCA periods)
+    fn validate_intent_terms(&self, intent: &ValidatedIntent) -> Result<(), Vec<u8>> {
+        // Validate addresses
+        if intent.token_in == Address::ZERO || intent.token_out == Address::ZERO {
+            return Err(b"Invalid token addresses".to_vec());
+        }
+        
+        if intent.token_in == intent.token_out {
+            return Err(b"Same token swap".to_vec());
+        }
+        
+        // Compliance lists
+        self.check_intent_access(intent)?;
+        
+        // Validate amounts
+        if intent.amount_in == U256::ZERO {
+            return Err(b"Amount must be greater than zero".to_vec());
+        }
+        
+        let min_amount = self.min_trade_amount.get();
+        let max_amount = self.max_trade_amount.get();
+        
+        if intent.amount_in < min_amount {
             return Err(b"Amount below minimum".to_vec());
         }
         
@@ -366,17 +1188,74 @@ impl SecureAquaFlowRouter {
             return Err(b"Slippage too high".to_vec());
         }
         
-        // Validate nonce
-        let expected_nonce = self.user_nonces.get(intent.user);
-        if intent.nonce != expected_nonce {
-            return Err(b"Invalid nonce".to_vec());
+        // Validate referral
+        if intent.referrer == Address::ZERO {
+            if intent.referral_fee_bps != U256::ZERO {
+                return Err(b"Referral fee without referrer".to_vec());
+            }
+        } else {
+            if intent.referrer == intent.user {
+                return Err(b"Self referral".to_vec());
+            }
+            
+            if intent.referral_fee_bps > self.max_referral_fee_bps.get() {
+                return Err(b"Referral fee too high".to_vec());
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Mark the intent's nonce as consumed
+    fn consume_nonce(&mut self, intent: &ValidatedIntent) -> Result<(), Vec<u8>> {
+        if intent.unordered_nonce {
+            let (word_pos, mask) = nonces::bitmap_position(intent.nonce);
+            let bitmap = self.nonce_bitmaps.get((intent.user, word_pos));
+            if nonces::is_bit_set(bitmap, mask) {
+                return Err(b"Nonce already used".to_vec());
+            }
+            self.nonce_bitmaps.setter((intent.user, word_pos)).set(bitmap | mask);
+        } else {
+            let new_nonce = self.user_nonces.get(intent.user) + U256::from(1);
+            self.user_nonces.setter(intent.user).set(new_nonce);
         }
         
         Ok(())
     }
     
+    /// Pre-trade anti-abuse, economic and (optionally) circuit breaker checks
+    /// A rejection reverts with the alert in the revert data (see alerts::rejection).
+    /// Volume counters are only advanced once every check has passed.
+    fn check_security(
+        &mut self,
+        intent: &ValidatedIntent,
+        circuit_breaker: bool,
+        mut meter: Option<&mut GasMeter>
+    ) -> Result<(), Vec<u8>> {
+        let daily_volume = self.check_abuse_protection(intent)?;
+        if let Some(meter) = meter.as_deref_mut() {
+            meter.record(GasStage::AbuseProtection, evm::gas_left());
+        }
+        
+        self.check_economic_limits(intent);
+        if let Some(meter) = meter.as_deref_mut() {
+            meter.record(GasStage::EconomicLimits, evm::gas_left());
+        }
+        
+        if circuit_breaker {
+            self.check_circuit_breaker(intent)?;
+        }
+        if let Some(meter) = meter.as_deref_mut() {
+            meter.record(GasStage::CircuitBreaker, evm::gas_left());
+        }
+        
+        self.daily_volume.setter(intent.user).set(daily_volume);
+        Ok(())
+    }
+    
     /// Anti-abuse protection
-    fn check_abuse_protection(&mut self, intent: &ValidatedIntent) -> Result<(), Vec<u8>> {
+    /// Returns the user's daily volume including this intent
+    fn check_abuse_protection(&mut self, intent: &ValidatedIntent) -> Result<U256, Vec<u8>> {
         let current_time = U256::from(block::timestamp());
         let last_reset = self.last_reset.get();
         
@@ -392,23 +1271,15 @@ impl SecureAquaFlowRouter {
         let max_volume = self.max_daily_volume.get();
         
         if new_volume > max_volume {
-            evm::log(SecurityAlert {
-                user: intent.user,
-                alert_type: "DAILY_VOLUME_EXCEEDED".to_string(),
-                severity: U256::from(2),
-                details: [0u8; 32].into(),
-            });
-            return Err(b"Daily volume limit exceeded".to_vec());
+            let details = self.emit_security_alert(intent.user, AlertCode::DailyVolumeExceeded, new_volume, max_volume, NO_POOL);
+            return Err(alerts::rejection("Daily volume limit exceeded", AlertCode::DailyVolumeExceeded, &details));
         }
         
-        // Update volume
-        self.daily_volume.setter(intent.user).set(new_volume);
-        
-        Ok(())
+        Ok(new_volume)
     }
     
     /// Economic security checks
-    fn check_economic_limits(&self, intent: &ValidatedIntent) -> Result<(), Vec<u8>> {
+    fn check_economic_limits(&self, intent: &ValidatedIntent) {
         // Check for flash loan attacks (simplified)
         let block_number = U256::from(block::number());
         
@@ -417,15 +1288,8 @@ impl SecureAquaFlowRouter {
         
         let reasonable_max = U256::from(1000000) * U256::from(10).pow(U256::from(18));
         if intent.amount_in > reasonable_max {
-            evm::log(SecurityAlert {
-                user: intent.user,
-                alert_type: "SUSPICIOUS_LARGE_AMOUNT".to_string(),
-                severity: U256::from(3),
-                details: [0u8; 32].into(),
-            });
+            self.emit_security_alert(intent.user, AlertCode::SuspiciousLargeAmount, intent.amount_in, reasonable_max, NO_POOL);
         }
-        
-        Ok(())
     }
     
     /// Circuit breaker check
@@ -437,20 +1301,60 @@ impl SecureAquaFlowRouter {
         if new_volume > threshold {
             self.paused.set(true);
             
-            evm::log(SecurityAlert {
-                user: intent.user,
-                alert_type: "CIRCUIT_BREAKER_TRIGGERED".to_string(),
-                severity: U256::from(5),
-                details: [0u8; 32].into(),
-            });
+            let details = self.emit_security_alert(intent.user, AlertCode::CircuitBreakerTriggered, new_volume, threshold, NO_POOL);
             
-            return Err(b"Circuit breaker triggered".to_vec());
+            return Err(alerts::rejection("Circuit breaker triggered", AlertCode::CircuitBreakerTriggered, &details));
         }
         
         self.total_volume_24h.set(new_volume);
         Ok(())
     }
     
+    /// Check user, recipient and tokens against the compliance lists
+    fn check_intent_access(&self, intent: &ValidatedIntent) -> Result<(), Vec<u8>> {
+        if self.denied_users.get(intent.user) || self.denied_users.get(intent.output_recipient()) {
+            return Err(b"Address denied".to_vec());
+        }
+        
+        if !self.is_token_permitted(intent.token_in) || !self.is_token_permitted(intent.token_out) {
+            return Err(b"Token not permitted".to_vec());
+        }
+        
+        Ok(())
+    }
+    
+    /// Check if caller can manage compliance lists
+    fn is_guardian(&self, caller: Address) -> bool {
+        caller == self.owner.get() || self.guardians.get(caller)
+    }
+    
+    /// Emit a SecurityAlert with structured details (see alerts::AlertDetails) and return the details
+    fn emit_security_alert(
+        &self,
+        user: Address,
+        code: AlertCode,
+        observed: U256,
+        threshold: U256,
+        pool_id: U256
+    ) -> AlertDetails {
+        let details = AlertDetails {
+            observed,
+            threshold,
+            pool_id,
+            block_number: U256::from(block::number()),
+        };
+        
+        evm::log(SecurityAlert {
+            user,
+            alert_code: code as u8,
+            alert_type: code.as_str().to_string(),
+            severity: U256::from(code.severity()),
+            details: details.encode().into(),
+        });
+        
+        details
+    }
+    
     /// Validate pool parameters
     fn validate_pool_parameters(
         &self,
@@ -468,6 +1372,10 @@ impl SecureAquaFlowRouter {
             return Err(b"Identical tokens".to_vec());
         }
         
+        if !self.is_token_permitted(token_a) || !self.is_token_permitted(token_b) {
+            return Err(b"Token not permitted".to_vec());
+        }
+        
         // Validate fee
         if fee_bps > U256::from(MAX_FEE_BPS) {
             return Err(b"Fee too high".to_vec());
@@ -489,12 +1397,21 @@ impl SecureAquaFlowRouter {
         Ok(())
     }
     
-    /// Get verified pool reserves
-    fn get_verified_pool_reserves(&self, pool_address: Address) -> Result<(U256, U256), Vec<u8>> {
-        // In production, would call actual pool contract with validation
-        // For hackathon, return reasonable values
-        let base_reserve = U256::from(MIN_LIQUIDITY) * U256::from(10).pow(U256::from(18));
-        Ok((base_reserve, base_reserve))
+    /// Get verified pool reserves from the pool contract through its adapter
+    fn get_verified_pool_reserves(
+        &self,
+        adapter: &dyn PoolAdapter,
+        pool_address: Address,
+        token_a: Address,
+        token_b: Address
+    ) -> Result<(U256, U256), Vec<u8>> {
+        let snapshot = adapter.fetch_state(pool_address, token_a, token_b)?;
+        
+        if snapshot.reserve_a == U256::ZERO || snapshot.reserve_b == U256::ZERO {
+            return Err(b"Insufficient liquidity".to_vec());
+        }
+        
+        Ok((snapshot.reserve_a, snapshot.reserve_b))
     }
     
     /// Find secure route with validation
@@ -513,114 +1430,121 @@ impl SecureAquaFlowRouter {
             return Err(b"No pools found for pair".to_vec());
         }
         
-        // Find best verified pool
-        let mut best_pool_id = U256::ZERO;
-        let mut best_output = U256::ZERO;
+        // Find best verified pool (pool ids start at 0, so "none" is tracked separately)
+        let mut candidates = Vec::new();
         
         for i in 0..pool_count {
             if let Some(pool_id) = pair_pools.get(i) {
-                let pool = self.pools.get(pool_id);
-                
                 // Only use verified pools
-                if !pool.is_verified {
+                if !self.pools.get(pool_id).is_verified {
                     continue;
                 }
                 
-                // Calculate output
-                if let Ok(output) = self.calculate_secure_swap_output(&pool, token_in, amount_in) {
-                    if output > best_output {
-                        best_output = output;
-                        best_pool_id = pool_id;
-                    }
+                // Quote from the pool's live state at its current effective fee
+                if let Ok(step) = self.quote_hop(pool_id, token_in, amount_in) {
+                    candidates.push((step.amount_out, step));
                 }
             }
         }
         
-        if best_pool_id == U256::ZERO {
-            return Err(b"No verified pools available".to_vec());
-        }
-        
-        let pool = self.pools.get(best_pool_id);
-        let price_impact = self.calculate_price_impact(&pool, token_in, amount_in);
-        
-        let route_step = SecureRouteStep {
-            pool_id: best_pool_id,
-            token_in,
-            token_out,
-            amount_in,
-            amount_out: best_output,
-            price_impact_bps: U256::from(price_impact),
-            verified: true,
-        };
+        let (_, route_step) = pool_index::best_output(candidates)
+            .ok_or(b"No verified pools available".to_vec())?;
         
         Ok(vec![route_step])
     }
     
-    /// Calculate secure swap output with overflow protection
-    fn calculate_secure_swap_output(
+    /// Read a pool's live state through its adapter
+    fn load_live_pool(&self, pool_id: U256) -> Result<LivePool, Vec<u8>> {
+        let pool = self.pools.get(pool_id);
+        let adapter = pool_adapter::adapter_for(pool.pool_type).map_err(|e| e.as_bytes().to_vec())?;
+        let snapshot = adapter.fetch_state(pool.pool_address, pool.token_a, pool.token_b)?;
+        Ok(LivePool { pool, adapter, snapshot })
+    }
+    
+    /// Quote one hop from the pool's live state
+    fn quote_hop(&self, pool_id: U256, token_in: Address, amount_in: U256) -> Result<SecureRouteStep, Vec<u8>> {
+        let live = self.load_live_pool(pool_id)?;
+        self.quote_live_pool(pool_id, &live, token_in, amount_in)
+    }
+    
+    /// Quote one hop through the pool's adapter
+    /// The pool charges its own fee; a dynamic-fee premium above it is withheld from amount_out by the router
+    fn quote_live_pool(
         &self,
-        pool: &SecurePool,
+        pool_id: U256,
+        live: &LivePool,
         token_in: Address,
         amount_in: U256
-    ) -> Result<U256, Vec<u8>> {
-        let (reserve_in, reserve_out) = if token_in == pool.token_a {
-            (pool.reserve_a, pool.reserve_b)
+    ) -> Result<SecureRouteStep, Vec<u8>> {
+        let pool = &live.pool;
+        let (a_to_b, token_out) = if token_in == pool.token_a {
+            (true, pool.token_b)
+        } else if token_in == pool.token_b {
+            (false, pool.token_a)
         } else {
-            (pool.reserve_b, pool.reserve_a)
+            return Err(b"Route token mismatch".to_vec());
         };
         
-        // Validate reserves
-        if reserve_in == U256::ZERO || reserve_out == U256::ZERO {
-            return Err(b"Insufficient liquidity".to_vec());
-        }
+        let view = live.view();
         
         // Check for reasonable liquidity
+        let (reserve_in, reserve_out) = view.reserves(a_to_b);
         let min_liquidity = U256::from(MIN_LIQUIDITY) * U256::from(10).pow(U256::from(18));
         if reserve_in < min_liquidity || reserve_out < min_liquidity {
             return Err(b"Pool liquidity too low".to_vec());
         }
         
-        // Apply fee with overflow protection
-        let fee_multiplier = U256::from(10000).checked_sub(pool.fee_bps)
-            .ok_or(b"Fee calculation overflow".to_vec())?;
-        
-        let amount_in_with_fee = amount_in.checked_mul(fee_multiplier)
-            .ok_or(b"Amount calculation overflow".to_vec())?
-            .checked_div(U256::from(10000))
-            .ok_or(b"Fee division error".to_vec())?;
-        
-        // Calculate output with overflow protection
-        let numerator = amount_in_with_fee.checked_mul(reserve_out)
-            .ok_or(b"Numerator overflow".to_vec())?;
+        let pool_out = live.adapter.quote_exact_in(&view, a_to_b, amount_in).map_err(|e| e.as_bytes().to_vec())?;
+        let spot_price = live.adapter.spot_price(&view, a_to_b).map_err(|e| e.as_bytes().to_vec())?;
         
-        let denominator = reserve_in.checked_add(amount_in_with_fee)
-            .ok_or(b"Denominator overflow".to_vec())?;
+        let fee_bps = self.effective_pool_fee(pool_id, pool);
+        let amount_out = pool_out - dynamic_fees::fee_surcharge(pool_out, pool.fee_bps, fee_bps);
         
-        if denominator == U256::ZERO {
-            return Err(b"Division by zero".to_vec());
+        Ok(SecureRouteStep {
+            pool_id,
+            token_in,
+            token_out,
+            amount_in,
+            amount_out,
+            price_impact_bps: pool_stats::price_impact_bps(amount_in, pool_out, spot_price, pool.fee_bps),
+            fee_bps,
+            verified: pool.is_verified,
+        })
+    }
+    
+    /// Fee charged by a pool right now: static fee_bps, or volatility-scaled if enabled
+    fn effective_pool_fee(&self, pool_id: U256, pool: &SecurePool) -> U256 {
+        let config = self.dynamic_fee_configs.get(pool_id);
+        if !config.enabled {
+            return pool.fee_bps;
         }
         
-        let amount_out = numerator.checked_div(denominator)
-            .ok_or(b"Output calculation error".to_vec())?;
-        
-        Ok(amount_out)
+        let volatility = self.pool_volatility.get(pool_id);
+        let max_fee_bps = core::cmp::min(config.max_fee_bps, U256::from(MAX_FEE_BPS));
+        dynamic_fees::effective_fee_bps(pool.fee_bps, volatility.volatility_bps, config.sensitivity_bps, max_fee_bps)
     }
     
-    /// Calculate price impact in basis points
-    fn calculate_price_impact(&self, pool: &SecurePool, token_in: Address, amount_in: U256) -> u32 {
-        let (reserve_in, reserve_out) = if token_in == pool.token_a {
-            (pool.reserve_a, pool.reserve_b)
-        } else {
-            (pool.reserve_b, pool.reserve_a)
-        };
+    /// Sample the pool's pre-trade spot price (token_b per token_a) once per block to update its volatility;
+    /// skipped while dynamic fees are disabled
+    fn observe_pool_price(&mut self, pool_id: U256, price: U256) {
+        if !self.dynamic_fee_configs.get(pool_id).enabled {
+            return;
+        }
+        
+        let current_block = U256::from(block::number());
+        let mut state = self.pool_volatility.get(pool_id);
+        if state.last_block == current_block {
+            return;
+        }
         
-        if reserve_in == U256::ZERO || reserve_out == U256::ZERO {
-            return MAX_PRICE_IMPACT_BPS;
+        if state.last_price != U256::ZERO {
+            let change_bps = dynamic_fees::price_change_bps(state.last_price, price);
+            state.volatility_bps = dynamic_fees::update_volatility(state.volatility_bps, change_bps);
         }
         
-        // Simplified price impact calculation
-        let impact_ratio = amount_in * U256::from(10000) / reserve_in;
-        std::cmp::min(impact_ratio.as_u32(), MAX_PRICE_IMPACT_BPS)
+        state.last_price = price;
+        state.last_block = current_block;
+        self.pool_volatility.setter(pool_id).set(state);
     }
     
     /// Validate route security
@@ -640,7 +1564,9 @@ impl SecureAquaFlowRouter {
             }
             
             // Check price impact
-            if step.price_impact_bps > intent.max_slippage_bps {
+            if step.price_impact_bps > intent.max_slippage_bps
+                || step.price_impact_bps > U256::from(MAX_PRICE_IMPACT_BPS)
+            {
                 return Err(b"Price impact too high".to_vec());
             }
         }
@@ -648,43 +1574,141 @@ impl SecureAquaFlowRouter {
         Ok(())
     }
     
-    /// Execute route with reentrancy protection
+    /// Execute route against the pools with reentrancy protection
+    /// Each hop swaps what the previous hop actually delivered; returns the token_out received by the router
+    /// `user` is the account the swap is made for (passed to user hooks)
     fn execute_secure_route(
         &mut self,
-        intent: &ValidatedIntent,
-        route: &[SecureRouteStep]
+        user: Address,
+        route: &[SecureRouteStep],
+        mut meter: Option<&mut GasMeter>
     ) -> Result<U256, Vec<u8>> {
-        // Update nonce first (reentrancy protection)
-        let new_nonce = self.user_nonces.get(intent.user) + U256::from(1);
-        self.user_nonces.setter(intent.user).set(new_nonce);
-        
-        let mut current_amount = intent.amount_in;
+        let mut current_amount = route.first().map(|step| step.amount_in).unwrap_or(U256::ZERO);
         
         for step in route {
-            // Update pool reserves
-            let mut pool = self.pools.get(step.pool_id);
+            // Hooks see the hop's actual amounts
+            let mut hop = SecureRouteStep { amount_in: current_amount, ..*step };
+            self.run_swap_hooks(HOOK_BEFORE_SWAP, user, &hop)?;
             
-            if step.token_in == pool.token_a {
-                pool.reserve_a = pool.reserve_a.checked_add(step.amount_in)
-                    .ok_or(b"Reserve overflow".to_vec())?;
-                pool.reserve_b = pool.reserve_b.checked_sub(step.amount_out)
-                    .ok_or(b"Reserve underflow".to_vec())?;
-            } else {
-                pool.reserve_b = pool.reserve_b.checked_add(step.amount_in)
-                    .ok_or(b"Reserve overflow".to_vec())?;
-                pool.reserve_a = pool.reserve_a.checked_sub(step.amount_out)
-                    .ok_or(b"Reserve underflow".to_vec())?;
-            }
+            hop.amount_out = self.execute_hop(&hop)?;
+            current_amount = hop.amount_out;
             
-            pool.last_updated = U256::from(block::timestamp());
-            self.pools.setter(step.pool_id).set(pool);
+            self.run_swap_hooks(HOOK_AFTER_SWAP, user, &hop)?;
             
-            current_amount = step.amount_out;
+            if let Some(meter) = meter.as_deref_mut() {
+                meter.record_hop(evm::gas_left());
+            }
         }
         
         Ok(current_amount)
     }
     
+    /// Swap `step.amount_in` held by the router through the pool
+    /// Returns the router's measured token_out balance increase, less the dynamic-fee premium (accrued as protocol fees);
+    /// output bounds are checked by the caller against this measured amount
+    fn execute_hop(&mut self, step: &SecureRouteStep) -> Result<U256, Vec<u8>> {
+        let live = self.load_live_pool(step.pool_id)?;
+        let view = live.view();
+        let a_to_b = step.token_in == live.pool.token_a;
+        
+        if let Ok(price) = live.adapter.spot_price(&view, true) {
+            self.observe_pool_price(step.pool_id, price);
+        }
+        
+        let router = contract::address();
+        let call = live.adapter.encode_swap(&view, &SwapParams {
+            a_to_b,
+            amount_in: step.amount_in,
+            min_amount_out: U256::ZERO,
+            sender: router,
+            recipient: router,
+            deadline: U256::from(block::timestamp()),
+        }).map_err(|e| e.as_bytes().to_vec())?;
+        
+        // Fund the swap the way the pool collects token_in
+        let payment = live.adapter.payment();
+        match payment {
+            SwapPayment::Prepay => tokens::push_tokens(self, step.token_in, live.pool.pool_address, step.amount_in)?,
+            SwapPayment::Allowance => tokens::approve(self, step.token_in, call.target, step.amount_in)?,
+            SwapPayment::Callback => {
+                self.active_swap_pool.set(live.pool.pool_address);
+                self.active_swap_token.set(step.token_in);
+                self.active_swap_amount.set(step.amount_in);
+            },
+        }
+        
+        let balance_before = tokens::balance_of(step.token_out, router)?;
+        
+        // SAFETY: the pool may call back into the router. Every external entry point except
+        // uniswapV3SwapCallback holds the reentrancy lock, and the callback only pays the active pool.
+        // The storage cache is flushed and cleared first so both frames read the same state.
+        unsafe {
+            RawCall::new()
+                .clear_storage_cache()
+                .call(call.target, &call.calldata)
+                .map_err(|_| b"Pool swap failed".to_vec())?;
+        }
+        
+        match payment {
+            SwapPayment::Prepay => {},
+            SwapPayment::Allowance => tokens::approve(self, step.token_in, call.target, U256::ZERO)?,
+            SwapPayment::Callback => {
+                if self.active_swap_pool.get() != Address::ZERO {
+                    return Err(b"Swap callback not received".to_vec());
+                }
+            },
+        }
+        
+        let received = tokens::balance_of(step.token_out, router)?.saturating_sub(balance_before);
+        
+        // The pool keeps its own fee; the dynamic premium above it accrues to the protocol
+        let surcharge = dynamic_fees::fee_surcharge(received, live.pool.fee_bps, step.fee_bps);
+        if surcharge > U256::ZERO {
+            let accrued = self.protocol_fees.get(step.token_out);
+            self.protocol_fees.setter(step.token_out).set(accrued + surcharge);
+        }
+        
+        // Record the last observed reserves
+        let mut pool = live.pool.clone();
+        pool.reserve_a = live.snapshot.reserve_a;
+        pool.reserve_b = live.snapshot.reserve_b;
+        pool.last_updated = U256::from(block::timestamp());
+        self.pools.setter(step.pool_id).set(pool);
+        
+        Ok(received - surcharge)
+    }
+    
+    /// Run the pool hook and then the user hook for one hop
+    fn run_swap_hooks(&mut self, stage: u8, user: Address, step: &SecureRouteStep) -> Result<(), Vec<u8>> {
+        let configs = [self.pool_hooks.get(step.pool_id), self.user_hooks.get(user)];
+        
+        let swap = HookSwap {
+            user,
+            pool_id: step.pool_id,
+            token_in: step.token_in,
+            token_out: step.token_out,
+            amount_in: step.amount_in,
+            amount_out: step.amount_out,
+        };
+        
+        for config in configs.iter() {
+            if !config.is_set() || !config.has_permission(stage) {
+                continue;
+            }
+            
+            if !hooks::invoke_hook(self, config, stage, &swap)? {
+                evm::log(HookFailed {
+                    hook: config.hook,
+                    user,
+                    pool_id: step.pool_id,
+                    stage,
+                });
+            }
+        }
+        
+        Ok(())
+    }
+    
     /// Update security metrics
     fn update_security_metrics(
         &mut self,
@@ -698,6 +1722,43 @@ impl SecureAquaFlowRouter {
         Ok(())
     }
     
+    /// Add an execution to the cumulative gas stats and emit the debug breakdown if enabled
+    /// Bookkeeping done here is not itself included in `gas_used`
+    fn record_gas_usage(
+        &mut self,
+        intent: &ValidatedIntent,
+        meter: &GasMeter,
+        event_gas: u64,
+        intrinsic_gas: u64,
+        gas_used: u64
+    ) {
+        self.total_gas_used.set(self.total_gas_used.get() + U256::from(gas_used));
+        self.metered_executions.set(self.metered_executions.get() + U256::from(1));
+        
+        if !self.gas_debug_enabled.get() {
+            return;
+        }
+        
+        let stages = [
+            GasStage::Validation,
+            GasStage::AbuseProtection,
+            GasStage::EconomicLimits,
+            GasStage::CircuitBreaker,
+            GasStage::RouteSearch,
+            GasStage::RouteValidation,
+            GasStage::Settlement,
+        ];
+        
+        evm::log(GasBreakdown {
+            user: intent.user,
+            stage_gas: stages.iter().map(|stage| U256::from(meter.stage(*stage))).collect(),
+            hop_gas: meter.hops().iter().map(|gas| U256::from(*gas)).collect(),
+            event_gas: U256::from(event_gas),
+            intrinsic_gas: U256::from(intrinsic_gas),
+            total_gas: U256::from(gas_used),
+        });
+    }
+    
     /// Calculate total price impact for route
     fn calculate_total_price_impact(&self, route: &[SecureRouteStep]) -> u32 {
         route.iter()
@@ -705,8 +1766,1698 @@ impl SecureAquaFlowRouter {
             .sum()
     }
     
-    /// Check if caller is authorized
-    fn is_authorized_caller(&self, caller: Address) -> bool {
-        caller == self.owner.get() || self.authorized_callers.get(caller)
-    }
-}
\ No newline at end of file
+    /// Charge the protocol fee on a swap output and return the net amount
+    fn charge_protocol_fee(&mut self, intent: &ValidatedIntent, amount_out: U256) -> U256 {
+        let split = fees::split_protocol_fee(
+            amount_out,
+            self.protocol_fee_bps.get(),
+            intent.referral_fee_bps
+        );
+        
+        let accrued = self.protocol_fees.get(intent.token_out);
+        self.protocol_fees.setter(intent.token_out).set(accrued + split.protocol_fee);
+        
+        if intent.referrer != Address::ZERO {
+            let key = (intent.referrer, intent.token_out);
+            let earned = self.referral_earnings.get(key);
+            self.referral_earnings.setter(key).set(earned + split.referral_fee);
+            
+            let volume = self.referral_volume.get(intent.referrer);
+            self.referral_volume.setter(intent.referrer).set(volume + intent.amount_in);
+            let count = self.referral_count.get(intent.referrer);
+            self.referral_count.setter(intent.referrer).set(count + U256::from(1));
+            
+            evm::log(ReferralCredited {
+                referrer: intent.referrer,
+                user: intent.user,
+                token: intent.token_out,
+                amount: split.referral_fee,
+            });
+        }
+        
+        split.net_amount
+    }
+    
+    /// Check if caller is authorized
+    fn is_authorized_caller(&self, caller: Address) -> bool {
+        caller == self.owner.get() || self.authorized_callers.get(caller)
+    }
+}
+
+// Swap execution callbacks
+#[external]
+impl SecureAquaFlowRouter {
+    /// Uniswap V3 swap callback: pays the pool of the hop being executed the token_in it is owed
+    /// Only that pool may call, once per hop, and only for the hop's exact input.
+    /// Runs inside execute_hop's pool call, so it does not take the reentrancy lock
+    #[selector(name = "uniswapV3SwapCallback")]
+    pub fn uniswap_v3_swap_callback(
+        &mut self,
+        amount0_delta: I256,
+        amount1_delta: I256,
+        _data: Bytes
+    ) -> Result<(), Vec<u8>> {
+        let pool = self.active_swap_pool.get();
+        if pool == Address::ZERO || msg::sender() != pool {
+            return Err(b"Unexpected swap callback".to_vec());
+        }
+        
+        // The positive delta is what the pool is owed
+        let owed = if amount0_delta.is_positive() { amount0_delta } else { amount1_delta };
+        if !owed.is_positive() || owed.into_raw() != self.active_swap_amount.get() {
+            return Err(b"Invalid callback amount".to_vec());
+        }
+        
+        let token = self.active_swap_token.get();
+        self.active_swap_pool.set(Address::ZERO);
+        self.active_swap_token.set(Address::ZERO);
+        self.active_swap_amount.set(U256::ZERO);
+        
+        tokens::push_tokens(self, token, pool, owed.into_raw())
+    }
+}
+
+// Limit order functions
+#[external]
+impl SecureAquaFlowRouter {
+    /// Park a limit order, escrowing token_in until filled, cancelled or expired
+    /// The limit price is min_amount_out / amount_in; deadline is the order expiry
+    pub fn place_limit_order(&mut self, intent: ValidatedIntent) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        // Same entry checks as a market intent
+        self.validate_secure_intent(&intent, msg::sender())?;
+        self.check_security(&intent, false, None)?;
+        
+        if intent.min_amount_out == U256::ZERO {
+            return Err(b"Limit price required".to_vec());
+        }
+        
+        self.consume_nonce(&intent)?;
+        
+        // Escrow token_in in the router
+        tokens::pull_tokens(self, intent.token_in, intent.user, intent.amount_in)?;
+        
+        let order_id = self.limit_order_count.get();
+        
+        evm::log(LimitOrderPlaced {
+            order_id,
+            user: intent.user,
+            token_in: intent.token_in,
+            token_out: intent.token_out,
+            amount_in: intent.amount_in,
+            min_amount_out: intent.min_amount_out,
+            deadline: intent.deadline,
+        });
+        
+        self.limit_orders.setter(order_id).set(LimitOrder {
+            intent,
+            filled_amount_in: U256::ZERO,
+            total_amount_out: U256::ZERO,
+            is_open: true,
+        });
+        self.limit_order_count.set(order_id + U256::from(1));
+        
+        self.exit_non_reentrant();
+        Ok(order_id)
+    }
+    
+    /// Fill all or part of a limit order (keeper entry point)
+    /// Returns the amount delivered to the order owner
+    pub fn fill_limit_order(&mut self, order_id: U256, fill_amount: U256) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if self.paused.get() {
+            return Err(b"Router is paused".to_vec());
+        }
+        
+        let mut order = self.limit_orders.get(order_id);
+        if !order.is_open {
+            return Err(b"Order not open".to_vec());
+        }
+        
+        if U256::from(block::timestamp()) > order.intent.deadline {
+            return Err(b"Order expired".to_vec());
+        }
+        
+        if !limit_orders::is_valid_fill(fill_amount, order.remaining(), self.min_trade_amount.get()) {
+            return Err(b"Invalid fill amount".to_vec());
+        }
+        
+        // The filled slice goes through the same pipeline as a market intent
+        let mut fill_intent = order.intent.clone();
+        fill_intent.amount_in = fill_amount;
+        
+        // Lists may have changed since the order was placed
+        self.check_intent_access(&fill_intent)?;
+        
+        self.check_circuit_breaker(&fill_intent)?;
+        
+        let route = self.find_secure_route(
+            fill_intent.token_in,
+            fill_intent.token_out,
+            fill_amount,
+            fill_intent.max_slippage_bps
+        )?;
+        self.validate_route_security(&route, &fill_intent)?;
+        
+        let min_out = limit_orders::min_out_for_fill(
+            order.intent.min_amount_out,
+            order.intent.amount_in,
+            fill_amount
+        ).map_err(|e| e.as_bytes().to_vec())?;
+        
+        // Swap the escrowed slice; the owner and keeper are paid only from what the pools delivered
+        let amount_out = self.execute_secure_route(fill_intent.user, &route, None)?;
+        
+        // Protocol fee and the referrer's share come off before the keeper bounty
+        let net_amount_out = self.charge_protocol_fee(&fill_intent, amount_out);
+        let (user_amount, bounty) = limit_orders::split_keeper_bounty(net_amount_out, self.keeper_bounty_bps.get());
+        
+        if user_amount < min_out {
+            return Err(b"Limit price not reached".to_vec());
+        }
+        
+        self.update_security_metrics(&fill_intent, amount_out)?;
+        
+        order.filled_amount_in = order.filled_amount_in + fill_amount;
+        order.total_amount_out = order.total_amount_out + user_amount;
+        if order.remaining() == U256::ZERO {
+            order.is_open = false;
+        }
+        
+        let recipient = order.intent.output_recipient();
+        let token_out = order.intent.token_out;
+        self.limit_orders.setter(order_id).set(order);
+        
+        let keeper = msg::sender();
+        tokens::push_tokens(self, token_out, recipient, user_amount)?;
+        tokens::push_tokens(self, token_out, keeper, bounty)?;
+        
+        evm::log(LimitOrderFilled {
+            order_id,
+            keeper,
+            amount_in: fill_amount,
+            amount_out: user_amount,
+            bounty,
+        });
+        
+        self.exit_non_reentrant();
+        Ok(user_amount)
+    }
+    
+    /// Cancel an open order and refund the unfilled token_in
+    /// The owner can cancel any time; anyone can clean up an expired order
+    pub fn cancel_limit_order(&mut self, order_id: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let mut order = self.limit_orders.get(order_id);
+        if !order.is_open {
+            return Err(b"Order not open".to_vec());
+        }
+        
+        let expired = U256::from(block::timestamp()) > order.intent.deadline;
+        if msg::sender() != order.intent.user && !expired {
+            return Err(b"Only order owner".to_vec());
+        }
+        
+        let refunded = order.remaining();
+        let user = order.intent.user;
+        let token_in = order.intent.token_in;
+        
+        order.is_open = false;
+        self.limit_orders.setter(order_id).set(order);
+        
+        tokens::push_tokens(self, token_in, user, refunded)?;
+        
+        evm::log(LimitOrderCancelled { order_id, refunded });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Get limit order by ID
+    pub fn get_limit_order(&self, order_id: U256) -> LimitOrder {
+        self.limit_orders.get(order_id)
+    }
+    
+    /// Set the keeper bounty paid from filled output (owner only)
+    pub fn set_keeper_bounty(&mut self, bounty_bps: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        if bounty_bps > U256::from(MAX_KEEPER_BOUNTY_BPS) {
+            return Err(b"Bounty too high".to_vec());
+        }
+        
+        self.keeper_bounty_bps.set(bounty_bps);
+        self.exit_non_reentrant();
+        Ok(())
+    }
+}
+
+// DCA schedule functions
+#[external]
+impl SecureAquaFlowRouter {
+    /// Create a recurring swap schedule funded by an escrowed budget
+    /// The first period is due immediately
+    pub fn create_dca_schedule(
+        &mut self,
+        token_in: Address,
+        token_out: Address,
+        budget: U256,
+        amount_per_period: U256,
+        interval: U256,
+        max_slippage_bps: U256,
+        min_amount_out_per_period: U256
+    ) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if self.paused.get() {
+            return Err(b"Router is paused".to_vec());
+        }
+        
+        if interval < U256::from(MIN_DCA_INTERVAL) {
+            return Err(b"Interval too short".to_vec());
+        }
+        
+        if amount_per_period == U256::ZERO || budget < amount_per_period {
+            return Err(b"Invalid DCA amounts".to_vec());
+        }
+        
+        let user = msg::sender();
+        let now = U256::from(block::timestamp());
+        
+        // Validate the per-period intent up front so a bad schedule fails at creation
+        let period_intent = self.dca_period_intent(
+            user,
+            token_in,
+            token_out,
+            amount_per_period,
+            min_amount_out_per_period,
+            max_slippage_bps,
+            now
+        );
+        self.validate_intent_terms(&period_intent)?;
+        
+        tokens::pull_tokens(self, token_in, user, budget)?;
+        
+        let schedule_id = self.dca_schedule_count.get();
+        self.dca_schedules.setter(schedule_id).set(DcaSchedule {
+            user,
+            token_in,
+            token_out,
+            amount_per_period,
+            min_amount_out_per_period,
+            interval,
+            max_slippage_bps,
+            budget_remaining: budget,
+            next_execution: now,
+            executions: U256::ZERO,
+            is_active: true,
+        });
+        self.dca_schedule_count.set(schedule_id + U256::from(1));
+        
+        evm::log(DcaScheduleCreated {
+            schedule_id,
+            user,
+            token_in,
+            token_out,
+            budget,
+            amount_per_period,
+            interval,
+        });
+        
+        self.exit_non_reentrant();
+        Ok(schedule_id)
+    }
+    
+    /// Execute the due period of a schedule (callable by anyone)
+    /// Returns the amount delivered to the schedule owner
+    pub fn execute_dca(&mut self, schedule_id: U256) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if self.paused.get() {
+            return Err(b"Router is paused".to_vec());
+        }
+        
+        let mut schedule = self.dca_schedules.get(schedule_id);
+        let now = U256::from(block::timestamp());
+        if !schedule.is_due(now) {
+            return Err(b"DCA period not due".to_vec());
+        }
+        
+        let amount_in = schedule.period_amount(self.min_trade_amount.get());
+        
+        // The price floor is per amount_per_period; scale it to this period's size
+        let min_amount_out = limit_orders::min_out_for_fill(
+            schedule.min_amount_out_per_period,
+            schedule.amount_per_period,
+            amount_in
+        ).map_err(|e| e.as_bytes().to_vec())?;
+        
+        let intent = self.dca_period_intent(
+            schedule.user,
+            schedule.token_in,
+            schedule.token_out,
+            amount_in,
+            min_amount_out,
+            schedule.max_slippage_bps,
+            now
+        );
+        
+        // Same security pipeline as execute_secure_intent, minus caller and nonce checks
+        self.validate_intent_terms(&intent)?;
+        self.check_security(&intent, true, None)?;
+        
+        let route = self.find_secure_route(
+            intent.token_in,
+            intent.token_out,
+            intent.amount_in,
+            intent.max_slippage_bps
+        )?;
+        self.validate_route_security(&route, &intent)?;
+        
+        let amount_out = self.execute_secure_route(intent.user, &route, None)?;
+        let net_amount_out = self.charge_protocol_fee(&intent, amount_out);
+        let (user_amount, bounty) = limit_orders::split_keeper_bounty(net_amount_out, self.keeper_bounty_bps.get());
+        if user_amount < intent.min_amount_out {
+            return Err(b"Insufficient output amount".to_vec());
+        }
+        
+        self.update_security_metrics(&intent, amount_out)?;
+        
+        schedule.budget_remaining = schedule.budget_remaining - amount_in;
+        schedule.executions = schedule.executions + U256::from(1);
+        schedule.next_execution = dca::next_execution_time(schedule.next_execution, schedule.interval, now);
+        if schedule.budget_remaining == U256::ZERO {
+            schedule.is_active = false;
+        }
+        
+        let next_execution = schedule.next_execution;
+        self.dca_schedules.setter(schedule_id).set(schedule);
+        
+        let keeper = msg::sender();
+        tokens::push_tokens(self, intent.token_out, intent.user, user_amount)?;
+        tokens::push_tokens(self, intent.token_out, keeper, bounty)?;
+        
+        evm::log(DcaExecuted {
+            schedule_id,
+            keeper,
+            amount_in,
+            amount_out: user_amount,
+            next_execution,
+        });
+        
+        self.exit_non_reentrant();
+        Ok(user_amount)
+    }
+    
+    /// Cancel a schedule and refund the unspent budget (owner only)
+    pub fn cancel_dca_schedule(&mut self, schedule_id: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let mut schedule = self.dca_schedules.get(schedule_id);
+        if msg::sender() != schedule.user {
+            return Err(b"Only schedule owner".to_vec());
+        }
+        
+        if !schedule.is_active {
+            return Err(b"Schedule not active".to_vec());
+        }
+        
+        let refunded = schedule.budget_remaining;
+        let user = schedule.user;
+        let token_in = schedule.token_in;
+        
+        schedule.budget_remaining = U256::ZERO;
+        schedule.is_active = false;
+        self.dca_schedules.setter(schedule_id).set(schedule);
+        
+        tokens::push_tokens(self, token_in, user, refunded)?;
+        
+        evm::log(DcaScheduleCancelled { schedule_id, refunded });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Get DCA schedule by ID
+    pub fn get_dca_schedule(&self, schedule_id: U256) -> DcaSchedule {
+        self.dca_schedules.get(schedule_id)
+    }
+}
+
+// DCA helpers
+impl SecureAquaFlowRouter {
+    /// Build the intent executed for a single DCA period
+    /// Executes atomically, so the deadline is the minimum the validator accepts.
+    /// Schedules carry no referrer, so periods pay the protocol fee only
+    fn dca_period_intent(
+        &self,
+        user: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        min_amount_out: U256,
+        max_slippage_bps: U256,
+        now: U256
+    ) -> ValidatedIntent {
+        ValidatedIntent {
+            user,
+            token_in,
+            token_out,
+            amount_in,
+            min_amount_out,
+            deadline: now + U256::from(INTENT_EXPIRY_BUFFER),
+            max_slippage_bps,
+            nonce: U256::ZERO,
+            unordered_nonce: false,
+            recipient: Address::ZERO,
+            referrer: Address::ZERO,
+            referral_fee_bps: U256::ZERO,
+        }
+    }
+}
+
+// Referral and fee functions
+#[external]
+impl SecureAquaFlowRouter {
+    /// Claim referral fees earned in `token`
+    pub fn claim_referral_fees(&mut self, token: Address) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let referrer = msg::sender();
+        let amount = self.referral_earnings.get((referrer, token));
+        if amount == U256::ZERO {
+            return Err(b"Nothing to claim".to_vec());
+        }
+        
+        self.referral_earnings.setter((referrer, token)).set(U256::ZERO);
+        tokens::push_tokens(self, token, referrer, amount)?;
+        
+        evm::log(ReferralFeesClaimed { referrer, token, amount });
+        
+        self.exit_non_reentrant();
+        Ok(amount)
+    }
+    
+    /// Send accrued protocol fees in `token` to the fee recipient (owner only)
+    pub fn claim_protocol_fees(&mut self, token: Address) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        let amount = self.protocol_fees.get(token);
+        if amount == U256::ZERO {
+            return Err(b"Nothing to claim".to_vec());
+        }
+        
+        let recipient = self.fee_recipient.get();
+        self.protocol_fees.setter(token).set(U256::ZERO);
+        tokens::push_tokens(self, token, recipient, amount)?;
+        
+        evm::log(ProtocolFeesClaimed { recipient, token, amount });
+        
+        self.exit_non_reentrant();
+        Ok(amount)
+    }
+    
+    /// Set the maximum referral share (owner only, bounded by the protocol fee)
+    pub fn set_max_referral_fee(&mut self, max_fee_bps: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        if max_fee_bps > self.protocol_fee_bps.get() {
+            return Err(b"Referral fee exceeds protocol fee".to_vec());
+        }
+        
+        self.max_referral_fee_bps.set(max_fee_bps);
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Claimable referral fees for a referrer in `token`
+    pub fn get_referral_earnings(&self, referrer: Address, token: Address) -> U256 {
+        self.referral_earnings.get((referrer, token))
+    }
+    
+    /// Referral stats: (attributed volume in token_in units, referred intents)
+    pub fn get_referral_stats(&self, referrer: Address) -> (U256, U256) {
+        (self.referral_volume.get(referrer), self.referral_count.get(referrer))
+    }
+    
+    /// Accrued, unclaimed protocol fees in `token`
+    pub fn get_protocol_fees(&self, token: Address) -> U256 {
+        self.protocol_fees.get(token)
+    }
+}
+
+// Compliance list functions
+#[external]
+impl SecureAquaFlowRouter {
+    /// Grant or revoke the guardian role (owner only)
+    pub fn set_guardian(&mut self, guardian: Address, enabled: bool) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        self.guardians.setter(guardian).set(enabled);
+        
+        evm::log(GuardianUpdated { guardian, enabled });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Add or remove a user from the deny list (owner or guardian)
+    pub fn set_user_denied(&mut self, user: Address, denied: bool) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let caller = msg::sender();
+        if !self.is_guardian(caller) {
+            return Err(b"Only guardian".to_vec());
+        }
+        
+        self.denied_users.setter(user).set(denied);
+        
+        evm::log(UserDenyListUpdated { user, updated_by: caller, denied });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Add or remove a token from the deny list (owner or guardian)
+    pub fn set_token_denied(&mut self, token: Address, denied: bool) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let caller = msg::sender();
+        if !self.is_guardian(caller) {
+            return Err(b"Only guardian".to_vec());
+        }
+        
+        self.denied_tokens.setter(token).set(denied);
+        
+        evm::log(TokenDenyListUpdated { token, updated_by: caller, denied });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Add or remove a token from the allowlist (owner or guardian)
+    pub fn set_token_allowed(&mut self, token: Address, allowed: bool) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let caller = msg::sender();
+        if !self.is_guardian(caller) {
+            return Err(b"Only guardian".to_vec());
+        }
+        
+        self.allowed_tokens.setter(token).set(allowed);
+        
+        evm::log(TokenAllowListUpdated { token, updated_by: caller, allowed });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Toggle allowlist-only mode for tokens (owner only)
+    pub fn set_token_allowlist_mode(&mut self, enabled: bool) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        self.token_allowlist_mode.set(enabled);
+        
+        evm::log(TokenAllowlistModeUpdated { enabled });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Check if a user is on the deny list
+    pub fn is_user_denied(&self, user: Address) -> bool {
+        self.denied_users.get(user)
+    }
+    
+    /// Check if a token can be traded or pooled under the current lists and mode
+    pub fn is_token_permitted(&self, token: Address) -> bool {
+        if self.denied_tokens.get(token) {
+            return false;
+        }
+        
+        !self.token_allowlist_mode.get() || self.allowed_tokens.get(token)
+    }
+}
+
+// Gas accounting functions
+#[external]
+impl SecureAquaFlowRouter {
+    /// Cumulative gas stats: (total gas, metered executions, average gas per execution)
+    pub fn get_gas_stats(&self) -> (U256, U256, U256) {
+        let total = self.total_gas_used.get();
+        let executions = self.metered_executions.get();
+        let average = if executions == U256::ZERO {
+            U256::ZERO
+        } else {
+            total / executions
+        };
+        
+        (total, executions, average)
+    }
+    
+    /// Enable or disable the GasBreakdown debug event (owner only)
+    pub fn set_gas_debug(&mut self, enabled: bool) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        self.gas_debug_enabled.set(enabled);
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+}
+
+// Swap hook functions
+#[external]
+impl SecureAquaFlowRouter {
+    /// Attach a hook to a pool (authorized callers only); zero address clears it
+    pub fn set_pool_hook(
+        &mut self,
+        pool_id: U256,
+        hook: Address,
+        permissions: u8,
+        gas_limit: U256
+    ) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if !self.is_authorized_caller(msg::sender()) {
+            return Err(b"Unauthorized caller".to_vec());
+        }
+        
+        if self.pools.get(pool_id).pool_address == Address::ZERO {
+            return Err(b"Pool does not exist".to_vec());
+        }
+        
+        hooks::validate_hook_config(hook, permissions, gas_limit)
+            .map_err(|e| e.as_bytes().to_vec())?;
+        
+        self.pool_hooks.setter(pool_id).set(HookConfig { hook, permissions, gas_limit });
+        
+        evm::log(PoolHookUpdated { pool_id, hook, permissions, gas_limit });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Attach a hook to the caller's own swaps; zero address clears it
+    pub fn set_user_hook(&mut self, hook: Address, permissions: u8, gas_limit: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        hooks::validate_hook_config(hook, permissions, gas_limit)
+            .map_err(|e| e.as_bytes().to_vec())?;
+        
+        let user = msg::sender();
+        self.user_hooks.setter(user).set(HookConfig { hook, permissions, gas_limit });
+        
+        evm::log(UserHookUpdated { user, hook, permissions, gas_limit });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Get the hook attached to a pool
+    pub fn get_pool_hook(&self, pool_id: U256) -> HookConfig {
+        self.pool_hooks.get(pool_id)
+    }
+    
+    /// Get the hook attached to a user
+    pub fn get_user_hook(&self, user: Address) -> HookConfig {
+        self.user_hooks.get(user)
+    }
+}
+
+// Dynamic fee functions
+#[external]
+impl SecureAquaFlowRouter {
+    /// Enable or disable volatility-scaled fees for a pool (authorized callers only)
+    pub fn set_dynamic_fee(
+        &mut self,
+        pool_id: U256,
+        enabled: bool,
+        sensitivity_bps: U256,
+        max_fee_bps: U256
+    ) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if !self.is_authorized_caller(msg::sender()) {
+            return Err(b"Unauthorized caller".to_vec());
+        }
+        
+        let pool = self.pools.get(pool_id);
+        if pool.pool_address == Address::ZERO {
+            return Err(b"Pool does not exist".to_vec());
+        }
+        
+        if max_fee_bps > U256::from(MAX_FEE_BPS) || max_fee_bps < pool.fee_bps {
+            return Err(b"Invalid max fee".to_vec());
+        }
+        
+        // Prices are not sampled while disabled, so re-seed rather than compare against a stale sample
+        if enabled && !self.dynamic_fee_configs.get(pool_id).enabled {
+            let mut state = self.pool_volatility.get(pool_id);
+            state.last_price = U256::ZERO;
+            state.last_block = U256::ZERO;
+            self.pool_volatility.setter(pool_id).set(state);
+        }
+        
+        self.dynamic_fee_configs.setter(pool_id).set(DynamicFeeConfig {
+            enabled,
+            sensitivity_bps,
+            max_fee_bps,
+        });
+        
+        evm::log(DynamicFeeConfigured {
+            pool_id,
+            enabled,
+            sensitivity_bps,
+            max_fee_bps,
+        });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Fee a swap through this pool would pay right now
+    pub fn get_effective_fee(&self, pool_id: U256) -> U256 {
+        let pool = self.pools.get(pool_id);
+        self.effective_pool_fee(pool_id, &pool)
+    }
+    
+    /// Get the pool's tracked volatility state
+    pub fn get_pool_volatility(&self, pool_id: U256) -> VolatilityState {
+        self.pool_volatility.get(pool_id)
+    }
+}
+
+// Commit-reveal functions
+#[external]
+impl SecureAquaFlowRouter {
+    /// Commit to an intent without revealing its terms
+    /// `commitment` = keccak256(intent_struct_hash ‖ salt), see commit_reveal::commitment_hash
+    /// Commitments are scoped to the committer, so copying one from the mempool reserves nothing
+    pub fn commit_intent(&mut self, commitment: B256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if self.paused.get() {
+            return Err(b"Router is paused".to_vec());
+        }
+        
+        let user = msg::sender();
+        if self.intent_commitments.get((user, commitment)).user != Address::ZERO {
+            return Err(b"Commitment already exists".to_vec());
+        }
+        
+        let commit_block = U256::from(block::number());
+        
+        self.intent_commitments.setter((user, commitment)).set(IntentCommitment {
+            user,
+            commit_block,
+            is_pending: true,
+        });
+        
+        evm::log(IntentCommitted { commitment, user, commit_block });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Reveal a committed intent and execute it (callable by anyone)
+    /// The commitment authenticates the user; deadline and nonce are enforced as usual
+    pub fn reveal_intent(&mut self, intent: ValidatedIntent, salt: B256) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let struct_hash = signatures::intent_struct_hash(&intent.abi_words());
+        let commitment = commit_reveal::commitment_hash(struct_hash, salt);
+        
+        let mut record = self.intent_commitments.get((intent.user, commitment));
+        if !record.is_pending {
+            return Err(b"Unknown commitment".to_vec());
+        }
+        
+        commit_reveal::check_reveal_window(
+            record.commit_block,
+            U256::from(block::number()),
+            self.reveal_delay_blocks.get(),
+            self.reveal_window_blocks.get()
+        ).map_err(|e| e.as_bytes().to_vec())?;
+        
+        let user = record.user;
+        record.is_pending = false;
+        self.intent_commitments.setter((user, commitment)).set(record);
+        
+        evm::log(IntentRevealed {
+            commitment,
+            user,
+            revealer: msg::sender(),
+        });
+        
+        let amount_out = self.execute_intent_pipeline(intent, user, Funding::Allowance)?;
+        
+        self.exit_non_reentrant();
+        Ok(amount_out)
+    }
+    
+    /// Cancel a pending commitment (committer only)
+    pub fn cancel_commitment(&mut self, commitment: B256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let user = msg::sender();
+        let mut record = self.intent_commitments.get((user, commitment));
+        if !record.is_pending {
+            return Err(b"Unknown commitment".to_vec());
+        }
+        
+        record.is_pending = false;
+        self.intent_commitments.setter((user, commitment)).set(record);
+        
+        evm::log(CommitmentCancelled { commitment, user });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Get a commitment record
+    pub fn get_commitment(&self, user: Address, commitment: B256) -> IntentCommitment {
+        self.intent_commitments.get((user, commitment))
+    }
+    
+    /// Set reveal delay and window in blocks (owner only)
+    pub fn set_reveal_params(&mut self, delay_blocks: U256, window_blocks: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        if delay_blocks == U256::ZERO || window_blocks == U256::ZERO {
+            return Err(b"Invalid reveal params".to_vec());
+        }
+        
+        self.reveal_delay_blocks.set(delay_blocks);
+        self.reveal_window_blocks.set(window_blocks);
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+}
+
+// Batch auction functions
+#[external]
+impl SecureAquaFlowRouter {
+    /// Escrow an intent in the open batch for its pair, opening a new batch if needed
+    /// The intent is filled or refunded when the batch is settled after its window
+    pub fn submit_batch_intent(&mut self, intent: ValidatedIntent) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        // Same entry checks as a market intent
+        self.validate_secure_intent(&intent, msg::sender())?;
+        self.check_security(&intent, true, None)?;
+        
+        let batch_id = self.open_batch_for(intent.token_in, intent.token_out);
+        let mut batch = self.batches.get(batch_id);
+        if intent.deadline < batch.end_time {
+            return Err(b"Deadline before batch close".to_vec());
+        }
+        
+        self.consume_nonce(&intent)?;
+        
+        // Escrow token_in in the router
+        tokens::pull_tokens(self, intent.token_in, intent.user, intent.amount_in)?;
+        
+        let order_id = self.batch_order_count.get();
+        
+        evm::log(BatchIntentSubmitted {
+            order_id,
+            batch_id,
+            user: intent.user,
+            token_in: intent.token_in,
+            amount_in: intent.amount_in,
+            min_amount_out: intent.min_amount_out,
+        });
+        
+        self.batch_orders.setter(order_id).set(BatchOrder {
+            intent,
+            batch_id,
+            amount_out: U256::ZERO,
+            status: BATCH_ORDER_PENDING,
+        });
+        self.batch_order_count.set(order_id + U256::from(1));
+        self.batch_order_ids.setter(batch_id).push(order_id);
+        
+        batch.order_count = batch.order_count + U256::from(1);
+        self.batches.setter(batch_id).set(batch);
+        
+        self.exit_non_reentrant();
+        Ok(order_id)
+    }
+    
+    /// Withdraw an intent from a batch that is still collecting (owner only)
+    pub fn cancel_batch_intent(&mut self, order_id: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let mut order = self.batch_orders.get(order_id);
+        if order.status != BATCH_ORDER_PENDING || order.intent.user == Address::ZERO {
+            return Err(b"Order not pending".to_vec());
+        }
+        
+        if msg::sender() != order.intent.user {
+            return Err(b"Only order owner".to_vec());
+        }
+        
+        let batch = self.batches.get(order.batch_id);
+        if U256::from(block::timestamp()) >= batch.end_time {
+            return Err(b"Batch closed".to_vec());
+        }
+        
+        let refunded = order.intent.amount_in;
+        let user = order.intent.user;
+        let token_in = order.intent.token_in;
+        
+        order.status = BATCH_ORDER_REFUNDED;
+        self.batch_orders.setter(order_id).set(order);
+        
+        tokens::push_tokens(self, token_in, user, refunded)?;
+        
+        evm::log(BatchIntentCancelled { order_id, refunded });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Refund a pending intent from a batch that was not settled in time (anyone can call)
+    /// Allowed once the intent's deadline has passed or the batch has been closed for BATCH_SETTLEMENT_GRACE,
+    /// so escrowed funds never depend on an authorized settler showing up
+    pub fn refund_batch_intent(&mut self, order_id: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let mut order = self.batch_orders.get(order_id);
+        if order.status != BATCH_ORDER_PENDING || order.intent.user == Address::ZERO {
+            return Err(b"Order not pending".to_vec());
+        }
+        
+        let batch = self.batches.get(order.batch_id);
+        let now = U256::from(block::timestamp());
+        if !batch_auction::is_refundable(now, batch.end_time, order.intent.deadline) {
+            return Err(b"Order not refundable yet".to_vec());
+        }
+        
+        let refunded = order.intent.amount_in;
+        let user = order.intent.user;
+        let token_in = order.intent.token_in;
+        let batch_id = order.batch_id;
+        
+        order.status = BATCH_ORDER_REFUNDED;
+        self.batch_orders.setter(order_id).set(order);
+        
+        tokens::push_tokens(self, token_in, user, refunded)?;
+        
+        evm::log(BatchOrderSettled {
+            order_id,
+            batch_id,
+            filled: false,
+            amount_out: U256::ZERO,
+        });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Settle a closed batch (authorized callers only)
+    /// Every intent clears at one uniform price set against the deepest verified pool's live state;
+    /// only the net imbalance is swapped through that pool, and unfillable intents are refunded.
+    /// The price comes from a pool that can be moved within a transaction, so settlement is restricted
+    pub fn settle_batch(&mut self, batch_id: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if !self.is_authorized_caller(msg::sender()) {
+            return Err(b"Unauthorized caller".to_vec());
+        }
+        
+        if self.paused.get() {
+            return Err(b"Router is paused".to_vec());
+        }
+        
+        let mut batch = self.batches.get(batch_id);
+        if batch.token0 == Address::ZERO || batch.settled {
+            return Err(b"Batch not settleable".to_vec());
+        }
+        
+        let now = U256::from(block::timestamp());
+        if now < batch.end_time {
+            return Err(b"Batch still collecting".to_vec());
+        }
+        
+        let order_ids: Vec<U256> = {
+            let ids = self.batch_order_ids.get(batch_id);
+            (0..ids.len()).filter_map(|i| ids.get(i)).collect()
+        };
+        let mut orders: Vec<BatchOrder> = order_ids.iter().map(|id| self.batch_orders.get(*id)).collect();
+        
+        // Expired, cancelled or newly denied orders are not cleared
+        let inputs: Vec<ClearingOrder> = orders.iter().map(|order| ClearingOrder {
+            amount_in: order.intent.amount_in,
+            min_amount_out: order.intent.min_amount_out,
+            sells_token0: order.intent.token_in == batch.token0,
+            eligible: order.status == BATCH_ORDER_PENDING
+                && now <= order.intent.deadline
+                && self.check_intent_access(&order.intent).is_ok(),
+        }).collect();
+        
+        let protocol_fee_bps = self.protocol_fee_bps.get();
+        let clearing = match self.find_clearing_pool(batch.token0, batch.token1) {
+            Some((pool_id, live)) => {
+                let clearing = batch_auction::clear_batch(&inputs, protocol_fee_bps, |sells_token0, amount| {
+                    let token_in = if sells_token0 { batch.token0 } else { batch.token1 };
+                    self.quote_live_pool(pool_id, &live, token_in, amount).ok().map(|step| step.amount_out)
+                });
+                
+                // Route the net imbalance through the pool; payouts assume the quoted output arrives
+                if clearing.pool_amount_in > U256::ZERO {
+                    let (token_in, token_out) = if clearing.pool_sells_token0 {
+                        (batch.token0, batch.token1)
+                    } else {
+                        (batch.token1, batch.token0)
+                    };
+                    
+                    let step = self.quote_live_pool(pool_id, &live, token_in, clearing.pool_amount_in)?;
+                    let received = self.execute_secure_route(contract::address(), &[step], None)?;
+                    if received < clearing.pool_amount_out {
+                        return Err(b"Insufficient output amount".to_vec());
+                    }
+                    
+                    let accrued = self.protocol_fees.get(token_out);
+                    self.protocol_fees.setter(token_out).set(accrued + (received - clearing.pool_amount_out));
+                }
+                
+                clearing
+            },
+            // Without a reference pool there is no price to clear at
+            None => Clearing::unfilled(inputs.len()),
+        };
+        
+        batch.settled = true;
+        self.batches.setter(batch_id).set(batch.clone());
+        
+        let mut filled_count = U256::ZERO;
+        let mut refunded_count = U256::ZERO;
+        
+        for (i, order) in orders.iter_mut().enumerate() {
+            if order.status != BATCH_ORDER_PENDING {
+                continue;
+            }
+            
+            let order_id = order_ids[i];
+            let filled = clearing.included[i];
+            
+            if filled {
+                order.amount_out = self.charge_protocol_fee(&order.intent, clearing.payouts[i]);
+                order.status = BATCH_ORDER_FILLED;
+                self.update_security_metrics(&order.intent, order.amount_out)?;
+                self.batch_orders.setter(order_id).set(order.clone());
+                
+                tokens::push_tokens(self, order.intent.token_out, order.intent.output_recipient(), order.amount_out)?;
+                filled_count = filled_count + U256::from(1);
+            } else {
+                order.status = BATCH_ORDER_REFUNDED;
+                self.batch_orders.setter(order_id).set(order.clone());
+                
+                tokens::push_tokens(self, order.intent.token_in, order.intent.user, order.intent.amount_in)?;
+                refunded_count = refunded_count + U256::from(1);
+            }
+            
+            evm::log(BatchOrderSettled {
+                order_id,
+                batch_id,
+                filled,
+                amount_out: order.amount_out,
+            });
+        }
+        
+        evm::log(BatchSettled {
+            batch_id,
+            clearing_price: clearing.price(),
+            matched_token0: clearing.matched_token0,
+            matched_token1: clearing.matched_token1,
+            pool_amount_in: clearing.pool_amount_in,
+            pool_amount_out: clearing.pool_amount_out,
+            filled_count,
+            refunded_count,
+        });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Get batch by ID
+    pub fn get_batch(&self, batch_id: U256) -> Batch {
+        self.batches.get(batch_id)
+    }
+    
+    /// Get batch order by ID
+    pub fn get_batch_order(&self, order_id: U256) -> BatchOrder {
+        self.batch_orders.get(order_id)
+    }
+    
+    /// Set the batch collection window in seconds (owner only)
+    pub fn set_batch_window(&mut self, window_seconds: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        if window_seconds == U256::ZERO || window_seconds > U256::from(MAX_BATCH_WINDOW) {
+            return Err(b"Invalid batch window".to_vec());
+        }
+        
+        self.batch_window.set(window_seconds);
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+}
+
+// Batch auction helpers
+impl SecureAquaFlowRouter {
+    /// Batch currently collecting for the pair, opening a new one if the last is closed or full
+    fn open_batch_for(&mut self, token_in: Address, token_out: Address) -> U256 {
+        let (token0, token1) = if token_in < token_out {
+            (token_in, token_out)
+        } else {
+            (token_out, token_in)
+        };
+        
+        let now = U256::from(block::timestamp());
+        let batch_id = self.open_batches.get((token0, token1));
+        let batch = self.batches.get(batch_id);
+        
+        // Checking the pair guards against the zero default of open_batches
+        if batch.token0 == token0
+            && batch.token1 == token1
+            && !batch.settled
+            && now < batch.end_time
+            && batch.order_count < U256::from(MAX_BATCH_ORDERS)
+        {
+            return batch_id;
+        }
+        
+        let batch_id = self.batch_count.get();
+        let end_time = now + self.batch_window.get();
+        
+        self.batches.setter(batch_id).set(Batch {
+            token0,
+            token1,
+            end_time,
+            order_count: U256::ZERO,
+            settled: false,
+        });
+        self.batch_count.set(batch_id + U256::from(1));
+        self.open_batches.setter((token0, token1)).set(batch_id);
+        
+        evm::log(BatchOpened { batch_id, token0, token1, end_time });
+        
+        batch_id
+    }
+    
+    /// Deepest verified pool for the pair, read live through its adapter
+    /// Its reserves set the clearing price for matched flow
+    fn find_clearing_pool(&self, token0: Address, token1: Address) -> Option<(U256, LivePool)> {
+        let pair_pools = self.pair_to_pools.get((token0, token1));
+        let mut best: Option<(U256, LivePool)> = None;
+        let mut best_depth = U256::ZERO;
+        
+        for i in 0..pair_pools.len() {
+            if let Some(pool_id) = pair_pools.get(i) {
+                if !self.pools.get(pool_id).is_verified {
+                    continue;
+                }
+                
+                let Ok(live) = self.load_live_pool(pool_id) else {
+                    continue;
+                };
+                
+                let depth = if live.pool.token_a == token0 { live.snapshot.reserve_a } else { live.snapshot.reserve_b };
+                if best.is_none() || depth > best_depth {
+                    best = Some((pool_id, live));
+                    best_depth = depth;
+                }
+            }
+        }
+        
+        best
+    }
+}
+
+// Solver competition functions
+#[external]
+impl SecureAquaFlowRouter {
+    /// Add to the caller's solver bond, registering them if needed
+    pub fn register_solver(&mut self, amount: U256) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let bond_token = self.solver_bond_token.get();
+        if bond_token == Address::ZERO {
+            return Err(b"Solver bonding not configured".to_vec());
+        }
+        
+        let solver = msg::sender();
+        tokens::pull_tokens(self, bond_token, solver, amount)?;
+        
+        let mut info = self.solvers.get(solver);
+        info.bond = info.bond + amount;
+        if info.bond < self.min_solver_bond.get() {
+            return Err(b"Bond below minimum".to_vec());
+        }
+        
+        // Re-bonding cancels a pending exit
+        info.is_active = true;
+        info.unlock_time = U256::ZERO;
+        let total_bond = info.bond;
+        self.solvers.setter(solver).set(info);
+        
+        evm::log(SolverBonded { solver, amount, total_bond });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Stop submitting solutions and start the unbonding period
+    pub fn request_solver_exit(&mut self) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let solver = msg::sender();
+        let mut info = self.solvers.get(solver);
+        if !info.is_active {
+            return Err(b"Solver not active".to_vec());
+        }
+        
+        let unlock_time = U256::from(block::timestamp()) + U256::from(SOLVER_UNBONDING_PERIOD);
+        info.is_active = false;
+        info.unlock_time = unlock_time;
+        self.solvers.setter(solver).set(info);
+        
+        evm::log(SolverExitRequested { solver, unlock_time });
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Withdraw the remaining bond once unbonding has finished
+    pub fn withdraw_solver_bond(&mut self) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        let solver = msg::sender();
+        let mut info = self.solvers.get(solver);
+        if info.is_active || info.unlock_time == U256::ZERO {
+            return Err(b"Exit not requested".to_vec());
+        }
+        
+        if U256::from(block::timestamp()) < info.unlock_time {
+            return Err(b"Bond still locked".to_vec());
+        }
+        
+        let amount = info.bond;
+        info.bond = U256::ZERO;
+        info.unlock_time = U256::ZERO;
+        self.solvers.setter(solver).set(info);
+        
+        let bond_token = self.solver_bond_token.get();
+        tokens::push_tokens(self, bond_token, solver, amount)?;
+        
+        evm::log(SolverBondWithdrawn { solver, amount });
+        
+        self.exit_non_reentrant();
+        Ok(amount)
+    }
+    
+    /// Escrow an intent and open it to solver competition
+    pub fn open_solver_auction(&mut self, intent: ValidatedIntent) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        // Same entry checks as a market intent
+        self.validate_secure_intent(&intent, msg::sender())?;
+        self.check_security(&intent, true, None)?;
+        
+        // The intent must stay executable through the winner's settlement window
+        let end_time = U256::from(block::timestamp()) + self.solver_window.get();
+        if intent.deadline < end_time + U256::from(SOLVER_SETTLEMENT_WINDOW) {
+            return Err(b"Deadline before auction close".to_vec());
+        }
+        
+        self.consume_nonce(&intent)?;
+        
+        // Escrow token_in in the router
+        tokens::pull_tokens(self, intent.token_in, intent.user, intent.amount_in)?;
+        
+        let auction_id = self.solver_auction_count.get();
+        
+        evm::log(SolverAuctionOpened {
+            auction_id,
+            user: intent.user,
+            token_in: intent.token_in,
+            token_out: intent.token_out,
+            amount_in: intent.amount_in,
+            min_amount_out: intent.min_amount_out,
+            end_time,
+        });
+        
+        self.solver_auctions.setter(auction_id).set(SolverAuction {
+            intent,
+            end_time,
+            solution_count: U256::ZERO,
+            winner: Address::ZERO,
+            amount_out: U256::ZERO,
+            is_open: true,
+        });
+        self.solver_auction_count.set(auction_id + U256::from(1));
+        
+        self.exit_non_reentrant();
+        Ok(auction_id)
+    }
+    
+    /// Submit a route (pool ids from token_in to token_out) and its claimed output
+    /// The claim is binding: if it wins, the solver must settle it within the settlement window or be slashed
+    pub fn submit_solution(
+        &mut self,
+        auction_id: U256,
+        pool_ids: Vec<U256>,
+        amount_out: U256
+    ) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if self.paused.get() {
+            return Err(b"Router is paused".to_vec());
+        }
+        
+        let solver = msg::sender();
+        let info = self.solvers.get(solver);
+        if !info.is_active || info.bond < self.min_solver_bond.get() {
+            return Err(b"Solver not bonded".to_vec());
+        }
+        
+        let mut auction = self.solver_auctions.get(auction_id);
+        if !auction.is_open || U256::from(block::timestamp()) >= auction.end_time {
+            return Err(b"Auction closed".to_vec());
+        }
+        
+        if amount_out < auction.intent.min_amount_out {
+            return Err(b"Solution below min output".to_vec());
+        }
+        
+        if pool_ids.is_empty() || pool_ids.len() > MAX_SOLUTION_HOPS {
+            return Err(b"Invalid route length".to_vec());
+        }
+        
+        if self.solver_submitted.get((auction_id, solver)) {
+            return Err(b"Solution already submitted".to_vec());
+        }
+        
+        if auction.solution_count >= U256::from(MAX_SOLUTIONS_PER_AUCTION) {
+            return Err(b"Auction full".to_vec());
+        }
+        
+        let solution_id = self.solution_count.get();
+        self.solutions.setter(solution_id).set(Solution {
+            auction_id,
+            solver,
+            amount_out,
+        });
+        
+        let mut route = self.solution_routes.setter(solution_id);
+        for pool_id in pool_ids {
+            route.push(pool_id);
+        }
+        
+        self.solution_count.set(solution_id + U256::from(1));
+        self.auction_solution_ids.setter(auction_id).push(solution_id);
+        self.solver_submitted.setter((auction_id, solver)).set(true);
+        
+        auction.solution_count = auction.solution_count + U256::from(1);
+        self.solver_auctions.setter(auction_id).set(auction);
+        
+        evm::log(SolutionSubmitted {
+            auction_id,
+            solution_id,
+            solver,
+            amount_out,
+        });
+        
+        self.exit_non_reentrant();
+        Ok(solution_id)
+    }
+    
+    /// Settle a closed auction
+    /// Ranked solutions take turns of SOLVER_SETTLEMENT_WINDOW, best claim first (see solver_auction::settlement_turn).
+    /// During a turn only its solver can settle, by executing its route; the call reverts unless the route delivers
+    /// the claim, so a market move costs a retry, not the bond. Solvers whose turn lapsed before the intent deadline
+    /// are slashed when the auction settles. Once no turn is open anyone can settle and the intent is refunded
+    pub fn settle_solver_auction(&mut self, auction_id: U256) -> Result<U256, Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if self.paused.get() {
+            return Err(b"Router is paused".to_vec());
+        }
+        
+        let mut auction = self.solver_auctions.get(auction_id);
+        if !auction.is_open {
+            return Err(b"Auction not open".to_vec());
+        }
+        
+        let now = U256::from(block::timestamp());
+        if now < auction.end_time {
+            return Err(b"Auction still collecting".to_vec());
+        }
+        
+        let intent = auction.intent.clone();
+        let solution_ids: Vec<U256> = {
+            let ids = self.auction_solution_ids.get(auction_id);
+            (0..ids.len()).filter_map(|i| ids.get(i)).collect()
+        };
+        let solutions: Vec<Solution> = solution_ids.iter().map(|id| self.solutions.get(*id)).collect();
+        
+        // Rank on output net of protocol fee so the winner always clears min_amount_out
+        let protocol_fee_bps = self.protocol_fee_bps.get();
+        let net_claims: Vec<U256> = solutions.iter()
+            .map(|solution| fees::split_protocol_fee(solution.amount_out, protocol_fee_bps, intent.referral_fee_bps).net_amount)
+            .collect();
+        let ranked = solver_auction::rank_solutions(&net_claims, intent.min_amount_out);
+        let turn = solver_auction::settlement_turn(ranked.len(), auction.end_time, intent.deadline, now);
+        
+        auction.is_open = false;
+        
+        // Lapsed turns are judged at their own window end, so settling late never lets them off
+        for &i in &ranked[..turn.slashable] {
+            self.slash_solver(solutions[i].solver, auction_id);
+        }
+        
+        let current = turn.current
+            .map(|k| ranked[k])
+            .filter(|_| self.check_intent_access(&intent).is_ok());
+        
+        let solver_reward = match current {
+            Some(i) => {
+                let solution = &solutions[i];
+                if msg::sender() != solution.solver {
+                    return Err(b"Only current solver".to_vec());
+                }
+                
+                let pool_ids: Vec<U256> = {
+                    let route = self.solution_routes.get(solution_ids[i]);
+                    (0..route.len()).filter_map(|j| route.get(j)).collect()
+                };
+                let route = self.verify_solution_route(&intent, &pool_ids)?;
+                
+                // The escrowed input is swapped for real; payouts come only from what the route delivered
+                let gross_amount_out = self.execute_secure_route(intent.user, &route, None)?;
+                if gross_amount_out < solution.amount_out {
+                    return Err(b"Solution under-delivered".to_vec());
+                }
+                
+                let net_amount_out = self.charge_protocol_fee(&intent, gross_amount_out);
+                let (user_amount, solver_reward) = solver_auction::split_surplus(
+                    net_amount_out,
+                    intent.min_amount_out,
+                    self.solver_surplus_share_bps.get()
+                );
+                
+                self.update_security_metrics(&intent, user_amount)?;
+                
+                auction.winner = solution.solver;
+                auction.amount_out = user_amount;
+                self.solver_auctions.setter(auction_id).set(auction.clone());
+                
+                tokens::push_tokens(self, intent.token_out, intent.output_recipient(), user_amount)?;
+                tokens::push_tokens(self, intent.token_out, solution.solver, solver_reward)?;
+                solver_reward
+            },
+            None => {
+                self.solver_auctions.setter(auction_id).set(auction.clone());
+                
+                tokens::push_tokens(self, intent.token_in, intent.user, intent.amount_in)?;
+                U256::ZERO
+            },
+        };
+        
+        evm::log(SolverAuctionSettled {
+            auction_id,
+            winner: auction.winner,
+            amount_out: auction.amount_out,
+            solver_reward,
+        });
+        
+        self.exit_non_reentrant();
+        Ok(auction.amount_out)
+    }
+    
+    /// Get solver bond and status
+    pub fn get_solver(&self, solver: Address) -> Solver {
+        self.solvers.get(solver)
+    }
+    
+    /// Get solver auction by ID
+    pub fn get_solver_auction(&self, auction_id: U256) -> SolverAuction {
+        self.solver_auctions.get(auction_id)
+    }
+    
+    /// Get a solution and its route
+    pub fn get_solution(&self, solution_id: U256) -> (Solution, Vec<U256>) {
+        let route = self.solution_routes.get(solution_id);
+        let pool_ids = (0..route.len()).filter_map(|i| route.get(i)).collect();
+        (self.solutions.get(solution_id), pool_ids)
+    }
+    
+    /// Set the solver bond token (owner only, once: existing bonds are held in it)
+    pub fn set_solver_bond_token(&mut self, token: Address) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        if self.solver_bond_token.get() != Address::ZERO {
+            return Err(b"Bond token already set".to_vec());
+        }
+        
+        if token == Address::ZERO {
+            return Err(b"Invalid bond token".to_vec());
+        }
+        
+        self.solver_bond_token.set(token);
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+    
+    /// Set solver bond minimum, solution window, surplus share and slash fraction (owner only)
+    pub fn set_solver_params(
+        &mut self,
+        min_bond: U256,
+        window_seconds: U256,
+        surplus_share_bps: U256,
+        slash_bps: U256
+    ) -> Result<(), Vec<u8>> {
+        self.enter_non_reentrant()?;
+        
+        if msg::sender() != self.owner.get() {
+            return Err(b"Only owner".to_vec());
+        }
+        
+        if window_seconds == U256::ZERO || window_seconds > U256::from(MAX_SOLVER_WINDOW) {
+            return Err(b"Invalid solver window".to_vec());
+        }
+        
+        if surplus_share_bps > U256::from(MAX_SOLVER_SURPLUS_SHARE_BPS) || slash_bps > U256::from(MAX_SOLVER_SLASH_BPS) {
+            return Err(b"Invalid solver params".to_vec());
+        }
+        
+        self.min_solver_bond.set(min_bond);
+        self.solver_window.set(window_seconds);
+        self.solver_surplus_share_bps.set(surplus_share_bps);
+        self.solver_slash_bps.set(slash_bps);
+        
+        self.exit_non_reentrant();
+        Ok(())
+    }
+}
+
+// Solver competition helpers
+impl SecureAquaFlowRouter {
+    /// Rebuild a solution's route against live pool state and check it is executable
+    fn verify_solution_route(
+        &self,
+        intent: &ValidatedIntent,
+        pool_ids: &[U256]
+    ) -> Result<Vec<SecureRouteStep>, Vec<u8>> {
+        if pool_ids.is_empty() || pool_ids.len() > MAX_SOLUTION_HOPS {
+            return Err(b"Invalid route length".to_vec());
+        }
+        
+        let mut route: Vec<SecureRouteStep> = Vec::with_capacity(pool_ids.len());
+        let mut token = intent.token_in;
+        let mut amount = intent.amount_in;
+        
+        for &pool_id in pool_ids {
+            // Quotes use pre-trade reserves, so a pool can only appear once
+            if route.iter().any(|step| step.pool_id == pool_id) {
+                return Err(b"Duplicate route pool".to_vec());
+            }
+            
+            let step = self.quote_hop(pool_id, token, amount)?;
+            if !step.verified {
+                return Err(b"Unverified route step".to_vec());
+            }
+            
+            token = step.token_out;
+            amount = step.amount_out;
+            route.push(step);
+        }
+        
+        if token != intent.token_out {
+            return Err(b"Route token mismatch".to_vec());
+        }
+        
+        self.validate_route_security(&route, intent)?;
+        
+        Ok(route)
+    }
+    
+    /// Slash a solver's bond into protocol fees for a failed settlement
+    fn slash_solver(&mut self, solver: Address, auction_id: U256) {
+        let mut info = self.solvers.get(solver);
+        let amount = solver_auction::slash_amount(info.bond, self.solver_slash_bps.get());
+        info.bond = info.bond - amount;
+        self.solvers.setter(solver).set(info);
+        
+        let bond_token = self.solver_bond_token.get();
+        let accrued = self.protocol_fees.get(bond_token);
+        self.protocol_fees.setter(bond_token).set(accrued + amount);
+        
+        evm::log(SolverSlashed { solver, auction_id, amount });
+    }
+}
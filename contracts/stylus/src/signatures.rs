@@ -0,0 +1,149 @@
+// AquaFlow Intent Signatures - EIP-712 hashing of ValidatedIntent and signer recovery
+// Lets relayers submit intents on behalf of users who signed them offchain
+
+use stylus_sdk::{
+    alloy_primitives::{keccak256, Address, B256, U256},
+    call::RawCall,
+    prelude::*,
+};
+
+/// EIP-712 type of ValidatedIntent (field order matches ValidatedIntent::abi_words)
+pub const INTENT_TYPE: &str = "ValidatedIntent(address user,address token_in,address token_out,uint256 amount_in,uint256 min_amount_out,uint256 deadline,uint256 max_slippage_bps,uint256 nonce,bool unordered_nonce,address recipient,address referrer,uint256 referral_fee_bps)";
+
+/// Witness type string passed to Permit2's permitWitnessTransferFrom
+/// Referenced structs are appended in alphabetical order per EIP-712
+pub const PERMIT2_WITNESS_TYPE: &str = "ValidatedIntent witness)TokenPermissions(address token,uint256 amount)ValidatedIntent(address user,address token_in,address token_out,uint256 amount_in,uint256 min_amount_out,uint256 deadline,uint256 max_slippage_bps,uint256 nonce,bool unordered_nonce,address recipient,address referrer,uint256 referral_fee_bps)";
+
+const DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const DOMAIN_NAME: &str = "AquaFlow";
+const DOMAIN_VERSION: &str = "1";
+
+/// secp256k1 curve order / 2, upper bound for `s` to reject malleable signatures
+const SECP256K1_HALF_ORDER: U256 = U256::from_limbs([
+    0xdfe92f46681b20a0,
+    0x5d576e7357a4501d,
+    0xffffffffffffffff,
+    0x7fffffffffffffff,
+]);
+
+/// ECDSA signature components
+#[derive(SolidityType, Clone)]
+pub struct Signature {
+    pub v: u8,
+    pub r: B256,
+    pub s: B256,
+}
+
+/// EIP-712 struct hash of an intent from its ABI words
+pub fn intent_struct_hash(words: &[U256]) -> B256 {
+    let mut data = Vec::with_capacity(32 * (words.len() + 1));
+    data.extend_from_slice(keccak256(INTENT_TYPE.as_bytes()).as_slice());
+    for word in words {
+        data.extend_from_slice(&word.to_be_bytes::<32>());
+    }
+    keccak256(&data)
+}
+
+/// EIP-712 domain separator for the router deployment
+pub fn domain_separator(chain_id: u64, verifying_contract: Address) -> B256 {
+    let mut data = Vec::with_capacity(32 * 5);
+    data.extend_from_slice(keccak256(DOMAIN_TYPE.as_bytes()).as_slice());
+    data.extend_from_slice(keccak256(DOMAIN_NAME.as_bytes()).as_slice());
+    data.extend_from_slice(keccak256(DOMAIN_VERSION.as_bytes()).as_slice());
+    data.extend_from_slice(&U256::from(chain_id).to_be_bytes::<32>());
+    data.extend_from_slice(&U256::from_be_slice(verifying_contract.as_slice()).to_be_bytes::<32>());
+    keccak256(&data)
+}
+
+/// Final digest signed by the user: keccak256("\x19\x01" ‖ domain ‖ struct_hash)
+pub fn typed_data_digest(domain: B256, struct_hash: B256) -> B256 {
+    let mut data = Vec::with_capacity(66);
+    data.extend_from_slice(&[0x19, 0x01]);
+    data.extend_from_slice(domain.as_slice());
+    data.extend_from_slice(struct_hash.as_slice());
+    keccak256(&data)
+}
+
+/// Reject malformed or malleable signatures before calling ecrecover
+pub fn check_signature(signature: &Signature) -> Result<(), &'static str> {
+    if signature.v != 27 && signature.v != 28 {
+        return Err("Invalid signature v");
+    }
+
+    if U256::from_be_bytes(signature.s.0) > SECP256K1_HALF_ORDER {
+        return Err("Invalid signature s");
+    }
+
+    Ok(())
+}
+
+/// Recover the signer of `digest` using the ecrecover precompile
+pub fn recover_signer(digest: B256, signature: &Signature) -> Result<Address, Vec<u8>> {
+    check_signature(signature).map_err(|e| e.as_bytes().to_vec())?;
+
+    let mut input = Vec::with_capacity(128);
+    input.extend_from_slice(digest.as_slice());
+    input.extend_from_slice(&U256::from(signature.v).to_be_bytes::<32>());
+    input.extend_from_slice(signature.r.as_slice());
+    input.extend_from_slice(signature.s.as_slice());
+
+    // SAFETY: static call to the ecrecover precompile, which cannot re-enter the router or touch its storage
+    let output = unsafe {
+        RawCall::new_static()
+            .call(Address::with_last_byte(1), &input)
+            .map_err(|_| b"ecrecover failed".to_vec())?
+    };
+
+    if output.len() != 32 {
+        return Err(b"Invalid signature".to_vec());
+    }
+
+    let signer = Address::from_slice(&output[12..32]);
+    if signer == Address::ZERO {
+        return Err(b"Invalid signature".to_vec());
+    }
+
+    Ok(signer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_witness_type_embeds_intent_type() {
+        assert!(PERMIT2_WITNESS_TYPE.ends_with(INTENT_TYPE));
+        assert!(PERMIT2_WITNESS_TYPE.starts_with("ValidatedIntent witness)TokenPermissions("));
+    }
+
+    #[test]
+    fn test_struct_hash_depends_on_every_word() {
+        let words = vec![U256::from(1); 12];
+        let mut changed = words.clone();
+        changed[11] = U256::from(2);
+
+        assert_ne!(intent_struct_hash(&words), intent_struct_hash(&changed));
+    }
+
+    #[test]
+    fn test_domain_separator_binds_chain_and_contract() {
+        let router = Address::from([9u8; 20]);
+        let other = Address::from([8u8; 20]);
+
+        assert_ne!(domain_separator(42161, router), domain_separator(42170, router));
+        assert_ne!(domain_separator(42161, router), domain_separator(42161, other));
+    }
+
+    #[test]
+    fn test_check_signature() {
+        let mut signature = Signature { v: 27, r: B256::ZERO, s: B256::ZERO };
+        assert!(check_signature(&signature).is_ok());
+
+        signature.v = 1;
+        assert!(check_signature(&signature).is_err());
+
+        signature.v = 28;
+        signature.s = B256::from(SECP256K1_HALF_ORDER + U256::from(1));
+        assert!(check_signature(&signature).is_err());
+    }
+}
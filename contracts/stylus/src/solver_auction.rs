@@ -0,0 +1,173 @@
+// AquaFlow Solver Auctions - Bonded solvers compete to fill an escrowed intent
+// The best claim wins a share of the surplus if its solver delivers it; winners that never settle are slashed
+
+use stylus_sdk::{
+    alloy_primitives::{Address, U256},
+    prelude::*,
+};
+
+use crate::secure_router::ValidatedIntent;
+
+/// Maximum solutions accepted per auction (bounds settlement gas)
+pub const MAX_SOLUTIONS_PER_AUCTION: u64 = 10;
+
+/// Maximum hops in a solution route
+pub const MAX_SOLUTION_HOPS: usize = 4;
+
+/// Delay between a solver's exit request and bond withdrawal, so pending auctions can still slash
+pub const SOLVER_UNBONDING_PERIOD: u64 = 86400;
+
+/// Caps for owner-configured surplus share and slash fraction
+pub const MAX_SOLVER_SURPLUS_SHARE_BPS: u64 = 5000;
+pub const MAX_SOLVER_SLASH_BPS: u64 = 10000;
+
+/// Longest configurable solution window in seconds
+pub const MAX_SOLVER_WINDOW: u64 = 600;
+
+/// Exclusive settlement window of each ranked solution; the best claim's window opens when the auction closes
+pub const SOLVER_SETTLEMENT_WINDOW: u64 = 300;
+
+const BPS_DENOMINATOR: u64 = 10000;
+
+/// Bonded solver
+#[derive(SolidityType, Clone)]
+pub struct Solver {
+    pub bond: U256,          // In the router's solver bond token
+    pub is_active: bool,     // False once an exit is requested
+    pub unlock_time: U256,   // Bond withdrawable from this timestamp after exit
+}
+
+/// Intent escrowed for solver competition
+#[derive(SolidityType, Clone)]
+pub struct SolverAuction {
+    pub intent: ValidatedIntent,
+    pub end_time: U256,       // Solutions accepted until this timestamp
+    pub solution_count: U256,
+    pub winner: Address,      // Zero until settled with a fill
+    pub amount_out: U256,     // Net amount delivered to the user
+    pub is_open: bool,        // False once settled
+}
+
+/// Solution claim; its route (pool ids) is stored separately
+#[derive(SolidityType, Clone)]
+pub struct Solution {
+    pub auction_id: U256,
+    pub solver: Address,
+    pub amount_out: U256, // Claimed gross output of the route
+}
+
+/// Order in which solutions are tried: highest claimed output first, earliest first on ties
+/// Claims below `min_amount_out` are dropped
+pub fn rank_solutions(claims: &[U256], min_amount_out: U256) -> Vec<usize> {
+    let mut ranked: Vec<usize> = (0..claims.len())
+        .filter(|&i| claims[i] >= min_amount_out)
+        .collect();
+
+    // Stable sort keeps submission order among equal claims
+    ranked.sort_by(|&a, &b| claims[b].cmp(&claims[a]));
+    ranked
+}
+
+/// Split net output between user and solver: the solver earns `share_bps` of the surplus
+/// above the user's min_amount_out. Returns (user_amount, solver_reward)
+pub fn split_surplus(net_amount_out: U256, min_amount_out: U256, share_bps: U256) -> (U256, U256) {
+    let surplus = net_amount_out.saturating_sub(min_amount_out);
+    let solver_reward = surplus * share_bps / U256::from(BPS_DENOMINATOR);
+    (net_amount_out - solver_reward, solver_reward)
+}
+
+/// Settlement state of a closed auction at a given time
+/// Ranked solutions settle in turn, each with its own SOLVER_SETTLEMENT_WINDOW starting at `end_time`
+#[derive(Debug, PartialEq, Eq)]
+pub struct SettlementTurn {
+    /// Ranked solutions whose window lapsed while the intent was still within its deadline; these are slashed
+    pub slashable: usize,
+    /// Ranked solution whose window is open now, if any and the intent has not expired
+    pub current: Option<usize>,
+}
+
+/// Settlement turn at `now` for `ranked_count` ranked solutions
+/// A window counts against its solver only if it ended by the intent deadline, whenever settle is called
+pub fn settlement_turn(ranked_count: usize, end_time: U256, deadline: U256, now: U256) -> SettlementTurn {
+    let window = U256::from(SOLVER_SETTLEMENT_WINDOW);
+    let to_count = |value: U256| usize::try_from(value).unwrap_or(usize::MAX).min(ranked_count);
+
+    let elapsed = to_count(now.saturating_sub(end_time) / window);
+    let executable = to_count(deadline.saturating_sub(end_time) / window);
+    let current = (elapsed < ranked_count && now <= deadline).then_some(elapsed);
+
+    SettlementTurn {
+        slashable: elapsed.min(executable),
+        current,
+    }
+}
+
+/// Amount slashed from a bond
+pub fn slash_amount(bond: U256, slash_bps: U256) -> U256 {
+    bond * slash_bps / U256::from(BPS_DENOMINATOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_solutions() {
+        let claims = [U256::from(100), U256::from(120), U256::from(90), U256::from(120)];
+        assert_eq!(rank_solutions(&claims, U256::from(95)), vec![1, 3, 0]);
+        assert!(rank_solutions(&claims, U256::from(200)).is_empty());
+    }
+
+    #[test]
+    fn test_split_surplus() {
+        // Surplus 100, solver keeps 10%
+        let (user, solver) = split_surplus(U256::from(1100), U256::from(1000), U256::from(1000));
+        assert_eq!(user, U256::from(1090));
+        assert_eq!(solver, U256::from(10));
+
+        // No surplus, no reward
+        let (user, solver) = split_surplus(U256::from(1000), U256::from(1000), U256::from(1000));
+        assert_eq!(user, U256::from(1000));
+        assert_eq!(solver, U256::ZERO);
+    }
+
+    #[test]
+    fn test_settlement_turn() {
+        let end = U256::from(1000);
+        let deadline = end + U256::from(3 * SOLVER_SETTLEMENT_WINDOW);
+        let at = |offset: u64| end + U256::from(offset);
+
+        // Best claim's window
+        assert_eq!(settlement_turn(2, end, deadline, at(0)), SettlementTurn { slashable: 0, current: Some(0) });
+        // Best claim lapsed, runner-up's turn
+        assert_eq!(settlement_turn(2, end, deadline, at(SOLVER_SETTLEMENT_WINDOW)), SettlementTurn { slashable: 1, current: Some(1) });
+        // Both lapsed, refund
+        assert_eq!(settlement_turn(2, end, deadline, at(2 * SOLVER_SETTLEMENT_WINDOW)), SettlementTurn { slashable: 2, current: None });
+        // No ranked solutions
+        assert_eq!(settlement_turn(0, end, deadline, at(0)), SettlementTurn { slashable: 0, current: None });
+    }
+
+    #[test]
+    fn test_settlement_turn_after_deadline() {
+        // Deadline right at the end of the first window: settle is only ever called after it
+        let end = U256::from(1000);
+        let deadline = end + U256::from(SOLVER_SETTLEMENT_WINDOW);
+        let late = deadline + U256::from(1);
+
+        // The winner's window lapsed with the intent executable, so it is still slashed
+        assert_eq!(settlement_turn(3, end, deadline, late), SettlementTurn { slashable: 1, current: None });
+        // Later windows fall past the deadline and never count, however late settle is called
+        let much_later = end + U256::from(10 * SOLVER_SETTLEMENT_WINDOW);
+        assert_eq!(settlement_turn(3, end, deadline, much_later), SettlementTurn { slashable: 1, current: None });
+
+        // Deadline inside the first window: nobody could be expected to settle
+        let short = end + U256::from(SOLVER_SETTLEMENT_WINDOW - 1);
+        assert_eq!(settlement_turn(3, end, short, much_later), SettlementTurn { slashable: 0, current: None });
+    }
+
+    #[test]
+    fn test_slash_amount() {
+        assert_eq!(slash_amount(U256::from(1000), U256::from(2500)), U256::from(250));
+        assert_eq!(slash_amount(U256::from(1000), U256::from(10000)), U256::from(1000));
+    }
+}
@@ -0,0 +1,197 @@
+// AquaFlow Token Helpers - ERC20 interface and escrow transfers
+// Every router path that custodies user funds goes through these helpers
+
+use stylus_sdk::{
+    alloy_primitives::{address, Address, B256, U256},
+    prelude::*,
+    call::Call,
+    contract,
+};
+use stylus_sdk::alloy_sol_types::sol;
+
+/// Canonical Permit2 deployment (same address on every chain)
+pub const PERMIT2: Address = address!("000000000022D473030F116dDEE9F6B43aC78BA3");
+
+// Permit2 signature-transfer structs, declared outside the interface because sol_interface! only accepts functions
+sol! {
+    #![sol(alloy_sol_types = stylus_sdk::alloy_sol_types)]
+
+    struct TokenPermissions {
+        address token;
+        uint256 amount;
+    }
+
+    struct PermitTransferFrom {
+        TokenPermissions permitted;
+        uint256 nonce;
+        uint256 deadline;
+    }
+
+    struct SignatureTransferDetails {
+        address to;
+        uint256 requestedAmount;
+    }
+}
+
+sol_interface! {
+    interface IERC20 {
+        function transfer(address to, uint256 amount) external returns (bool);
+        function transferFrom(address from, address to, uint256 amount) external returns (bool);
+        function approve(address spender, uint256 amount) external returns (bool);
+        function balanceOf(address account) external view returns (uint256);
+        function decimals() external view returns (uint8);
+    }
+
+    interface IERC20Permit {
+        function permit(address owner, address spender, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external;
+    }
+
+    interface IPermit2 {
+        function permitWitnessTransferFrom(PermitTransferFrom permit, SignatureTransferDetails transferDetails, address owner, bytes32 witness, string witnessTypeString, bytes signature) external;
+    }
+}
+
+/// Pull tokens from `from` into the router
+pub fn pull_tokens(
+    storage: &mut impl TopLevelStorage,
+    token: Address,
+    from: Address,
+    amount: U256
+) -> Result<(), Vec<u8>> {
+    if amount == U256::ZERO {
+        return Ok(());
+    }
+
+    let balance_before = balance_of(token, contract::address())?;
+
+    let success = IERC20::new(token)
+        .transfer_from(Call::new_in(storage), from, contract::address(), amount)
+        .map_err(|_| b"Token transfer failed".to_vec())?;
+
+    if !success {
+        return Err(b"Token transfer failed".to_vec());
+    }
+
+    check_received(token, balance_before, amount)
+}
+
+/// Send tokens held by the router to `to`
+pub fn push_tokens(
+    storage: &mut impl TopLevelStorage,
+    token: Address,
+    to: Address,
+    amount: U256
+) -> Result<(), Vec<u8>> {
+    if amount == U256::ZERO {
+        return Ok(());
+    }
+
+    let success = IERC20::new(token)
+        .transfer(Call::new_in(storage), to, amount)
+        .map_err(|_| b"Token transfer failed".to_vec())?;
+
+    if !success {
+        return Err(b"Token transfer failed".to_vec());
+    }
+
+    Ok(())
+}
+
+/// Set the router's allowance for `spender`
+pub fn approve(
+    storage: &mut impl TopLevelStorage,
+    token: Address,
+    spender: Address,
+    amount: U256
+) -> Result<(), Vec<u8>> {
+    let success = IERC20::new(token)
+        .approve(Call::new_in(storage), spender, amount)
+        .map_err(|_| b"Token approval failed".to_vec())?;
+
+    if !success {
+        return Err(b"Token approval failed".to_vec());
+    }
+
+    Ok(())
+}
+
+/// Require the router's balance of `token` to have grown by `amount` since `balance_before`
+/// Escrow is pooled, so a short transfer (fee-on-transfer tokens) would otherwise be covered by other users' funds
+fn check_received(token: Address, balance_before: U256, amount: U256) -> Result<(), Vec<u8>> {
+    let received = balance_of(token, contract::address())?.saturating_sub(balance_before);
+    if received < amount {
+        return Err(b"Token transfer short".to_vec());
+    }
+
+    Ok(())
+}
+
+/// Read a token balance
+pub fn balance_of(token: Address, account: Address) -> Result<U256, Vec<u8>> {
+    IERC20::new(token)
+        .balance_of(Call::new(), account)
+        .map_err(|_| b"Token balance unavailable".to_vec())
+}
+
+/// Read a token's decimals
+pub fn token_decimals(token: Address) -> Result<u8, Vec<u8>> {
+    IERC20::new(token)
+        .decimals(Call::new())
+        .map_err(|_| b"Token decimals unavailable".to_vec())
+}
+
+/// Submit an EIP-2612 permit granting the router `amount` of `token`
+/// Failure is ignored: a front-run permit still leaves the allowance in place,
+/// and a missing allowance makes the following pull revert anyway
+pub fn try_permit(
+    storage: &mut impl TopLevelStorage,
+    token: Address,
+    owner: Address,
+    amount: U256,
+    deadline: U256,
+    v: u8,
+    r: B256,
+    s: B256
+) {
+    let _ = IERC20Permit::new(token)
+        .permit(Call::new_in(storage), owner, contract::address(), amount, deadline, v, r, s);
+}
+
+/// Pull tokens via Permit2 signature transfer, binding the transfer to `witness`
+pub fn pull_with_permit2(
+    storage: &mut impl TopLevelStorage,
+    token: Address,
+    owner: Address,
+    amount: U256,
+    nonce: U256,
+    deadline: U256,
+    witness: B256,
+    witness_type: &str,
+    signature: Vec<u8>
+) -> Result<(), Vec<u8>> {
+    let permit = PermitTransferFrom {
+        permitted: TokenPermissions { token, amount },
+        nonce,
+        deadline,
+    };
+    let details = SignatureTransferDetails {
+        to: contract::address(),
+        requestedAmount: amount,
+    };
+
+    let balance_before = balance_of(token, contract::address())?;
+
+    IPermit2::new(PERMIT2)
+        .permit_witness_transfer_from(
+            Call::new_in(storage),
+            permit,
+            details,
+            owner,
+            witness,
+            witness_type.to_string(),
+            signature.into()
+        )
+        .map_err(|_| b"Permit2 transfer failed".to_vec())?;
+
+    check_received(token, balance_before, amount)
+}
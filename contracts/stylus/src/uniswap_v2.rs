@@ -0,0 +1,232 @@
+// AquaFlow Uniswap V2 Adapter - Reserve reads for constant-product pairs
+// Pair reserves are ordered by token0/token1 and normalized to the registry's token_a/token_b
+
+use stylus_sdk::{
+    alloy_primitives::{Address, U256},
+    prelude::*,
+    call::Call,
+};
+
+use crate::pool_adapter::{self, PoolAdapter, PoolSnapshot, PoolView, SwapCall, SwapParams, SwapPayment, PRICE_PRECISION};
+
+/// PoolInfo.pool_type for Uniswap V2 pairs and forks
+pub const POOL_TYPE: u8 = 0;
+
+const BPS_DENOMINATOR: u64 = 10000;
+
+sol_interface! {
+    interface IUniswapV2Pair {
+        function token0() external view returns (address);
+        function token1() external view returns (address);
+        function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast);
+    }
+}
+
+/// Map pair reserves (token0, token1 order) onto (token_a, token_b)
+pub fn normalize_reserves(
+    token0: Address,
+    token1: Address,
+    reserve0: U256,
+    reserve1: U256,
+    token_a: Address,
+    token_b: Address
+) -> Result<(U256, U256), &'static str> {
+    if token0 == token_a && token1 == token_b {
+        Ok((reserve0, reserve1))
+    } else if token0 == token_b && token1 == token_a {
+        Ok((reserve1, reserve0))
+    } else {
+        Err("Pool tokens mismatch")
+    }
+}
+
+/// Constant-product exact-input quote with the fee taken from the input
+pub fn quote_exact_in(
+    reserve_in: U256,
+    reserve_out: U256,
+    amount_in: U256,
+    fee_bps: U256
+) -> Result<U256, &'static str> {
+    if reserve_in == U256::ZERO || reserve_out == U256::ZERO {
+        return Err("Insufficient liquidity");
+    }
+
+    if fee_bps >= U256::from(BPS_DENOMINATOR) {
+        return Err("Invalid fee");
+    }
+
+    let amount_in_with_fee = amount_in * (U256::from(BPS_DENOMINATOR) - fee_bps);
+    let numerator = amount_in_with_fee * reserve_out;
+    let denominator = reserve_in * U256::from(BPS_DENOMINATOR) + amount_in_with_fee;
+    Ok(numerator / denominator)
+}
+
+/// Constant-product exact-output quote: the smallest input whose quote_exact_in covers `amount_out`
+pub fn quote_exact_out(
+    reserve_in: U256,
+    reserve_out: U256,
+    amount_out: U256,
+    fee_bps: U256
+) -> Result<U256, &'static str> {
+    if reserve_in == U256::ZERO || amount_out >= reserve_out {
+        return Err("Insufficient liquidity");
+    }
+
+    if fee_bps >= U256::from(BPS_DENOMINATOR) {
+        return Err("Invalid fee");
+    }
+
+    let numerator = reserve_in * amount_out * U256::from(BPS_DENOMINATOR);
+    let denominator = (reserve_out - amount_out) * (U256::from(BPS_DENOMINATOR) - fee_bps);
+    Ok((numerator + denominator - U256::from(1)) / denominator)
+}
+
+/// Read reserves of a V2 pair as (reserve_a, reserve_b)
+/// Reverting or non-conforming contracts are reported as errors rather than zero reserves
+pub fn fetch_reserves(
+    pool_address: Address,
+    token_a: Address,
+    token_b: Address
+) -> Result<(U256, U256), Vec<u8>> {
+    let pair = IUniswapV2Pair::new(pool_address);
+
+    let token0 = pair.token_0(Call::new())
+        .map_err(|_| b"Pool token0 unavailable".to_vec())?;
+    let token1 = pair.token_1(Call::new())
+        .map_err(|_| b"Pool token1 unavailable".to_vec())?;
+    let (reserve0, reserve1, _) = pair.get_reserves(Call::new())
+        .map_err(|_| b"Pool reserves unavailable".to_vec())?;
+
+    normalize_reserves(token0, token1, U256::from(reserve0), U256::from(reserve1), token_a, token_b)
+        .map_err(|e| e.as_bytes().to_vec())
+}
+
+/// Adapter for Uniswap V2 pairs; keeps no state beyond the registry's reserves
+pub struct UniswapV2Adapter;
+
+impl PoolAdapter for UniswapV2Adapter {
+    fn pool_type(&self) -> u8 {
+        POOL_TYPE
+    }
+
+    fn fetch_state(&self, pool_address: Address, token_a: Address, token_b: Address) -> Result<PoolSnapshot, Vec<u8>> {
+        let (reserve_a, reserve_b) = fetch_reserves(pool_address, token_a, token_b)?;
+        Ok(PoolSnapshot { reserve_a, reserve_b, state: Vec::new() })
+    }
+
+    fn quote_exact_in(&self, pool: &PoolView, a_to_b: bool, amount_in: U256) -> Result<U256, &'static str> {
+        let (reserve_in, reserve_out) = pool.reserves(a_to_b);
+        quote_exact_in(reserve_in, reserve_out, amount_in, pool.fee_bps)
+    }
+
+    fn quote_exact_out(&self, pool: &PoolView, a_to_b: bool, amount_out: U256) -> Result<U256, &'static str> {
+        let (reserve_in, reserve_out) = pool.reserves(a_to_b);
+        quote_exact_out(reserve_in, reserve_out, amount_out, pool.fee_bps)
+    }
+
+    fn spot_price(&self, pool: &PoolView, a_to_b: bool) -> Result<U256, &'static str> {
+        let (reserve_in, reserve_out) = pool.reserves(a_to_b);
+        if reserve_in == U256::ZERO {
+            return Err("Insufficient liquidity");
+        }
+        Ok(reserve_out * U256::from(PRICE_PRECISION) / reserve_in)
+    }
+
+    fn payment(&self) -> SwapPayment {
+        SwapPayment::Prepay
+    }
+
+    /// pair.swap(amount0Out, amount1Out, to, ""); token_in must already be transferred to the pair
+    fn encode_swap(&self, pool: &PoolView, swap: &SwapParams) -> Result<SwapCall, &'static str> {
+        let amount_out = self.quote_exact_in(pool, swap.a_to_b, swap.amount_in)?;
+        if amount_out < swap.min_amount_out {
+            return Err("Insufficient output amount");
+        }
+
+        // Pairs sort their tokens by address
+        let (_, token_out) = pool.tokens(swap.a_to_b);
+        let (amount0_out, amount1_out) = if token_out == pool.token_a.min(pool.token_b) {
+            (amount_out, U256::ZERO)
+        } else {
+            (U256::ZERO, amount_out)
+        };
+
+        Ok(SwapCall {
+            target: pool.pool_address,
+            calldata: pool_adapter::encode_call("swap(uint256,uint256,address,bytes)", &[
+                amount0_out,
+                amount1_out,
+                pool_adapter::address_word(swap.recipient),
+                U256::from(128), // Offset of the empty callback data
+                U256::ZERO,
+            ]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_reserves() {
+        let token0 = Address::from([1u8; 20]);
+        let token1 = Address::from([2u8; 20]);
+        let other = Address::from([3u8; 20]);
+        let (r0, r1) = (U256::from(100), U256::from(200));
+
+        assert_eq!(normalize_reserves(token0, token1, r0, r1, token0, token1), Ok((r0, r1)));
+        assert_eq!(normalize_reserves(token0, token1, r0, r1, token1, token0), Ok((r1, r0)));
+        assert_eq!(normalize_reserves(token0, token1, r0, r1, token0, other), Err("Pool tokens mismatch"));
+    }
+
+    #[test]
+    fn test_quote_exact_in() {
+        // 1000/1000 pool, 100 in at 0.3%: 997000 * 1000 / (10000000 + 997000) = 90
+        let out = quote_exact_in(U256::from(1000), U256::from(1000), U256::from(100), U256::from(30)).unwrap();
+        assert_eq!(out, U256::from(90));
+        assert!(quote_exact_in(U256::ZERO, U256::from(1000), U256::from(100), U256::from(30)).is_err());
+    }
+
+    #[test]
+    fn test_quote_exact_out() {
+        // Inverse of the case above: 100 in buys 90, and 99 in would buy only 89
+        let (reserve, fee) = (U256::from(1000), U256::from(30));
+        let amount_in = quote_exact_out(reserve, reserve, U256::from(90), fee).unwrap();
+        assert!(quote_exact_in(reserve, reserve, amount_in, fee).unwrap() >= U256::from(90));
+        assert!(quote_exact_in(reserve, reserve, amount_in - U256::from(1), fee).unwrap() < U256::from(90));
+        assert_eq!(quote_exact_out(reserve, reserve, reserve, fee), Err("Insufficient liquidity"));
+    }
+
+    #[test]
+    fn test_encode_swap_orders_outputs_by_token0() {
+        let low = Address::from([1u8; 20]);
+        let high = Address::from([2u8; 20]);
+        let pool = PoolView {
+            pool_address: Address::from([9u8; 20]),
+            token_a: high,
+            token_b: low,
+            reserve_a: U256::from(1000),
+            reserve_b: U256::from(1000),
+            fee_bps: U256::from(30),
+            state: &[],
+        };
+        let swap = SwapParams {
+            a_to_b: true,
+            amount_in: U256::from(100),
+            min_amount_out: U256::from(90),
+            sender: Address::ZERO,
+            recipient: Address::from([3u8; 20]),
+            deadline: U256::ZERO,
+        };
+
+        // Buying token_b = token0, so amount0Out carries the output
+        let call = UniswapV2Adapter.encode_swap(&pool, &swap).unwrap();
+        assert_eq!(call.target, pool.pool_address);
+        assert_eq!(U256::from_be_slice(&call.calldata[4..36]), U256::from(90));
+        assert_eq!(U256::from_be_slice(&call.calldata[36..68]), U256::ZERO);
+
+        let greedy = SwapParams { min_amount_out: U256::from(91), ..swap };
+        assert!(UniswapV2Adapter.encode_swap(&pool, &greedy).is_err());
+    }
+}
@@ -0,0 +1,641 @@
+// AquaFlow Uniswap V3 Adapter - Concentrated liquidity state reads and swap math
+// Quotes walk the current range and cross at most one initialized tick in each direction
+
+use stylus_sdk::{
+    alloy_primitives::{aliases::I24, Address, U256, U512},
+    prelude::*,
+    call::Call,
+};
+
+use crate::pool_adapter::{self, PoolAdapter, PoolSnapshot, PoolView, SwapCall, SwapParams, SwapPayment, PRICE_PRECISION};
+
+/// PoolInfo.pool_type for Uniswap V3 pools and forks
+pub const POOL_TYPE: u8 = 1;
+
+/// Tick bounds from Uniswap's TickMath
+pub const MIN_TICK: i32 = -887272;
+pub const MAX_TICK: i32 = 887272;
+
+const Q96_SHIFT: usize = 96;
+const BPS_DENOMINATOR: u64 = 10000;
+const STATE_WORDS: usize = 11;
+
+sol_interface! {
+    interface IUniswapV3Pool {
+        function token0() external view returns (address);
+        function token1() external view returns (address);
+        function slot0() external view returns (uint160 sqrtPriceX96, int24 tick, uint16 observationIndex, uint16 observationCardinality, uint16 observationCardinalityNext, uint8 feeProtocol, bool unlocked);
+        function liquidity() external view returns (uint128);
+        function tickSpacing() external view returns (int24);
+        function tickBitmap(int16 wordPosition) external view returns (uint256);
+        function ticks(int24 tick) external view returns (uint128 liquidityGross, int128 liquidityNet, uint256 feeGrowthOutside0X128, uint256 feeGrowthOutside1X128, int56 tickCumulativeOutside, uint160 secondsPerLiquidityOutsideX128, uint32 secondsOutside, bool initialized);
+    }
+}
+
+/// Snapshot of a V3 pool around its current tick
+/// `*_below` is used by token0 -> token1 swaps (price falls), `*_above` by token1 -> token0
+#[derive(SolidityType, Clone, Debug, PartialEq, Eq)]
+pub struct V3PoolState {
+    pub sqrt_price_x96: U256,
+    pub tick: i32,
+    pub tick_spacing: i32,
+    pub liquidity: U256,           // Active liquidity in the current range
+    pub token0_is_a: bool,         // Whether PoolInfo.token_a is the pool's token0
+    pub tick_below: i32,           // Next initialized tick (or word boundary) at or below `tick`
+    pub liquidity_net_below: i128,
+    pub range_end_below: i32,      // Furthest tick a quote may reach after crossing tick_below
+    pub tick_above: i32,           // Next initialized tick (or word boundary) above `tick`
+    pub liquidity_net_above: i128,
+    pub range_end_above: i32,      // Furthest tick a quote may reach after crossing tick_above
+}
+
+/// a * b / denominator with a 512-bit intermediate, rounding down
+pub fn mul_div(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator == U256::ZERO {
+        return None;
+    }
+    let product: U512 = a.widening_mul(b);
+    narrow(product / U512::from(denominator))
+}
+
+/// a * b / denominator with a 512-bit intermediate, rounding up
+pub fn mul_div_rounding_up(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator == U256::ZERO {
+        return None;
+    }
+    let product: U512 = a.widening_mul(b);
+    let denominator = U512::from(denominator);
+    let mut quotient = product / denominator;
+    if product % denominator != U512::ZERO {
+        quotient += U512::from(1);
+    }
+    narrow(quotient)
+}
+
+fn narrow(value: U512) -> Option<U256> {
+    (value <= U512::from(U256::MAX)).then(|| value.to::<U256>())
+}
+
+fn div_rounding_up(a: U256, b: U256) -> U256 {
+    let quotient = a / b;
+    if a % b == U256::ZERO { quotient } else { quotient + U256::from(1) }
+}
+
+/// sqrt(1.0001^tick) * 2^96, bit-for-bit port of TickMath.getSqrtRatioAtTick
+pub fn sqrt_ratio_at_tick(tick: i32) -> Result<U256, &'static str> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err("Tick out of range");
+    }
+
+    const FACTORS: [(u32, u128); 19] = [
+        (0x2, 0xfff97272373d413259a46990580e213a),
+        (0x4, 0xfff2e50f5f656932ef12357cf3c7fdcc),
+        (0x8, 0xffe5caca7e10e4e61c3624eaa0941cd0),
+        (0x10, 0xffcb9843d60f6159c9db58835c926644),
+        (0x20, 0xff973b41fa98c081472e6896dfb254c0),
+        (0x40, 0xff2ea16466c96a3843ec78b326b52861),
+        (0x80, 0xfe5dee046a99a2a811c461f1969c3053),
+        (0x100, 0xfcbe86c7900a88aedcffc83b479aa3a4),
+        (0x200, 0xf987a7253ac413176f2b074cf7815e54),
+        (0x400, 0xf3392b0822b70005940c7a398e4b70f3),
+        (0x800, 0xe7159475a2c29b7443b29c7fa6e889d9),
+        (0x1000, 0xd097f3bdfd2022b8845ad8f792aa5825),
+        (0x2000, 0xa9f746462d870fdf8a65dc1f90e061e5),
+        (0x4000, 0x70d869a156d2a1b890bb3df62baf32f7),
+        (0x8000, 0x31be135f97d08fd981231505542fcfa6),
+        (0x10000, 0x9aa508b5b7a84e1c677de54f3e99bc9),
+        (0x20000, 0x5d6af8dedb81196699c329225ee604),
+        (0x40000, 0x2216e584f5fa1ea926041bedfe98),
+        (0x80000, 0x48a170391f7dc42444e8fa2),
+    ];
+
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio = if abs_tick & 0x1 != 0 {
+        U256::from(0xfffcb933bd6fad37aa2d162d1a594001u128)
+    } else {
+        U256::from(1) << 128
+    };
+
+    for (bit, factor) in FACTORS {
+        if abs_tick & bit != 0 {
+            ratio = (ratio * U256::from(factor)) >> 128;
+        }
+    }
+
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Round up so the result never understates the price
+    let shifted = ratio >> 32;
+    if ratio % (U256::from(1) << 32) == U256::ZERO {
+        Ok(shifted)
+    } else {
+        Ok(shifted + U256::from(1))
+    }
+}
+
+/// Compressed tick (tick / spacing, rounded toward negative infinity)
+fn compress(tick: i32, tick_spacing: i32) -> i32 {
+    let compressed = tick / tick_spacing;
+    if tick < 0 && tick % tick_spacing != 0 { compressed - 1 } else { compressed }
+}
+
+/// tickBitmap word holding the bit searched by `next_initialized_tick_within_word`
+pub fn bitmap_word_position(tick: i32, tick_spacing: i32, lte: bool) -> i16 {
+    let compressed = compress(tick, tick_spacing) + if lte { 0 } else { 1 };
+    (compressed >> 8) as i16
+}
+
+/// Port of TickBitmap.nextInitializedTickWithinOneWord over an already fetched word
+/// Returns the next initialized tick, or the word boundary if none is initialized
+pub fn next_initialized_tick_within_word(word: U256, tick: i32, tick_spacing: i32, lte: bool) -> (i32, bool) {
+    let compressed = compress(tick, tick_spacing);
+
+    if lte {
+        let bit_pos = (compressed & 0xff) as usize;
+        let mask = (U256::from(1) << bit_pos) - U256::from(1) + (U256::from(1) << bit_pos);
+        let masked = word & mask;
+
+        if masked != U256::ZERO {
+            let msb = 255 - masked.leading_zeros() as i32;
+            ((compressed - (bit_pos as i32 - msb)) * tick_spacing, true)
+        } else {
+            ((compressed - bit_pos as i32) * tick_spacing, false)
+        }
+    } else {
+        let next = compressed + 1;
+        let bit_pos = (next & 0xff) as usize;
+        let mask = !((U256::from(1) << bit_pos) - U256::from(1));
+        let masked = word & mask;
+
+        if masked != U256::ZERO {
+            let lsb = masked.trailing_zeros() as i32;
+            ((next + (lsb - bit_pos as i32)) * tick_spacing, true)
+        } else {
+            ((next + (255 - bit_pos as i32)) * tick_spacing, false)
+        }
+    }
+}
+
+/// Apply a tick's liquidity_net when crossing it in the swap direction
+fn cross_tick(liquidity: U256, liquidity_net: i128, zero_for_one: bool) -> Result<U256, &'static str> {
+    // Moving down subtracts liquidity_net, moving up adds it
+    let adds = (liquidity_net >= 0) != zero_for_one;
+    let delta = U256::from(liquidity_net.unsigned_abs());
+
+    if adds {
+        Ok(liquidity + delta)
+    } else {
+        liquidity.checked_sub(delta).ok_or("Invalid liquidity net")
+    }
+}
+
+/// Input (after fee) needed to move the price from `sqrt_price` to `sqrt_target` within one range
+fn amount_to_target(sqrt_price: U256, sqrt_target: U256, liquidity: U256, zero_for_one: bool) -> Option<U256> {
+    if zero_for_one {
+        // token0 in: L * 2^96 * (sqrtP - sqrtT) / (sqrtP * sqrtT), rounded up
+        let numerator = mul_div_rounding_up(liquidity << Q96_SHIFT, sqrt_price - sqrt_target, sqrt_price)?;
+        Some(div_rounding_up(numerator, sqrt_target))
+    } else {
+        // token1 in: L * (sqrtT - sqrtP) / 2^96, rounded up
+        mul_div_rounding_up(liquidity, sqrt_target - sqrt_price, U256::from(1) << Q96_SHIFT)
+    }
+}
+
+/// Output released when the price moves from `sqrt_from` to `sqrt_to` within one range
+fn amount_out_between(sqrt_from: U256, sqrt_to: U256, liquidity: U256, zero_for_one: bool) -> Option<U256> {
+    if zero_for_one {
+        // token1 out: L * (sqrtFrom - sqrtTo) / 2^96
+        mul_div(liquidity, sqrt_from - sqrt_to, U256::from(1) << Q96_SHIFT)
+    } else {
+        // token0 out: L * 2^96 * (sqrtTo - sqrtFrom) / (sqrtTo * sqrtFrom)
+        let numerator = mul_div(liquidity << Q96_SHIFT, sqrt_to - sqrt_from, sqrt_to)?;
+        Some(numerator / sqrt_from)
+    }
+}
+
+/// Price reached by spending `amount` (after fee) inside one range
+fn next_sqrt_price(sqrt_price: U256, liquidity: U256, amount: U256, zero_for_one: bool) -> Option<U256> {
+    if zero_for_one {
+        // L * 2^96 / (L * 2^96 / sqrtP + amount), rounded up
+        let numerator = liquidity << Q96_SHIFT;
+        Some(div_rounding_up(numerator, numerator / sqrt_price + amount))
+    } else {
+        // sqrtP + amount * 2^96 / L
+        Some(sqrt_price + mul_div(amount, U256::from(1) << Q96_SHIFT, liquidity)?)
+    }
+}
+
+/// Exact-input quote. Walks the current range, crosses the adjacent tick and continues
+/// until the range end recorded in the state; larger swaps are rejected rather than guessed.
+/// Only as fresh as `state`: a state read before the price moved out of its range quotes the old range,
+/// so callers quoting for execution should pass a state fetched in the same transaction
+pub fn quote_exact_in(
+    state: &V3PoolState,
+    zero_for_one: bool,
+    amount_in: U256,
+    fee_bps: U256
+) -> Result<U256, &'static str> {
+    if fee_bps >= U256::from(BPS_DENOMINATOR) {
+        return Err("Invalid fee");
+    }
+
+    let mut remaining = amount_in * (U256::from(BPS_DENOMINATOR) - fee_bps) / U256::from(BPS_DENOMINATOR);
+    let mut sqrt_price = state.sqrt_price_x96;
+    let mut liquidity = state.liquidity;
+    let mut amount_out = U256::ZERO;
+
+    let boundaries = if zero_for_one {
+        [(state.tick_below, state.liquidity_net_below), (state.range_end_below, 0)]
+    } else {
+        [(state.tick_above, state.liquidity_net_above), (state.range_end_above, 0)]
+    };
+
+    for (i, (tick, liquidity_net)) in boundaries.iter().enumerate() {
+        let sqrt_target = sqrt_ratio_at_tick((*tick).clamp(MIN_TICK, MAX_TICK))?;
+
+        let moving_toward_target = if zero_for_one { sqrt_target < sqrt_price } else { sqrt_target > sqrt_price };
+        if liquidity > U256::ZERO && moving_toward_target {
+            let needed = amount_to_target(sqrt_price, sqrt_target, liquidity, zero_for_one)
+                .ok_or("V3 math overflow")?;
+
+            if remaining <= needed {
+                // Swap finishes inside this range
+                let sqrt_next = next_sqrt_price(sqrt_price, liquidity, remaining, zero_for_one)
+                    .ok_or("V3 math overflow")?;
+                amount_out += amount_out_between(sqrt_price, sqrt_next, liquidity, zero_for_one)
+                    .ok_or("V3 math overflow")?;
+                return Ok(amount_out);
+            }
+
+            amount_out += amount_out_between(sqrt_price, sqrt_target, liquidity, zero_for_one)
+                .ok_or("V3 math overflow")?;
+            remaining -= needed;
+        }
+
+        sqrt_price = sqrt_target;
+        if i == 0 {
+            liquidity = cross_tick(liquidity, *liquidity_net, zero_for_one)?;
+        }
+    }
+
+    Err("Swap exceeds known tick range")
+}
+
+/// Virtual constant-product reserves (token0, token1) of the active range
+pub fn virtual_reserves(sqrt_price_x96: U256, liquidity: U256) -> (U256, U256) {
+    if sqrt_price_x96 == U256::ZERO {
+        return (U256::ZERO, U256::ZERO);
+    }
+
+    let q96 = U256::from(1) << Q96_SHIFT;
+    let reserve0 = mul_div(liquidity, q96, sqrt_price_x96).unwrap_or(U256::MAX);
+    let reserve1 = mul_div(liquidity, sqrt_price_x96, q96).unwrap_or(U256::MAX);
+    (reserve0, reserve1)
+}
+
+/// Marginal price before fees in PRICE_PRECISION units of token_out per token_in
+pub fn spot_price(sqrt_price_x96: U256, zero_for_one: bool) -> Result<U256, &'static str> {
+    if sqrt_price_x96 == U256::ZERO {
+        return Err("Pool not initialized");
+    }
+
+    let q96 = U256::from(1) << Q96_SHIFT;
+    let precision = U256::from(PRICE_PRECISION);
+    let price = if zero_for_one {
+        // token1 per token0 = sqrtP^2 / 2^192
+        mul_div(sqrt_price_x96, sqrt_price_x96, q96).and_then(|p| mul_div(p, precision, q96))
+    } else {
+        mul_div(q96, precision, sqrt_price_x96).and_then(|p| mul_div(p, q96, sqrt_price_x96))
+    };
+    price.ok_or("V3 math overflow")
+}
+
+/// Registry state words of a V3 pool, in V3PoolState field order
+pub fn encode_state(state: &V3PoolState) -> Vec<U256> {
+    vec![
+        state.sqrt_price_x96,
+        pool_adapter::int_word(state.tick as i128),
+        pool_adapter::int_word(state.tick_spacing as i128),
+        state.liquidity,
+        U256::from(state.token0_is_a as u8),
+        pool_adapter::int_word(state.tick_below as i128),
+        pool_adapter::int_word(state.liquidity_net_below),
+        pool_adapter::int_word(state.range_end_below as i128),
+        pool_adapter::int_word(state.tick_above as i128),
+        pool_adapter::int_word(state.liquidity_net_above),
+        pool_adapter::int_word(state.range_end_above as i128),
+    ]
+}
+
+/// V3PoolState stored by encode_state
+pub fn decode_state(words: &[U256]) -> Result<V3PoolState, &'static str> {
+    if words.len() != STATE_WORDS {
+        return Err("Invalid pool state");
+    }
+
+    let tick = |i: usize| pool_adapter::word_int(words[i]) as i32;
+    Ok(V3PoolState {
+        sqrt_price_x96: words[0],
+        tick: tick(1),
+        tick_spacing: tick(2),
+        liquidity: words[3],
+        token0_is_a: words[4] != U256::ZERO,
+        tick_below: tick(5),
+        liquidity_net_below: pool_adapter::word_int(words[6]),
+        range_end_below: tick(7),
+        tick_above: tick(8),
+        liquidity_net_above: pool_adapter::word_int(words[9]),
+        range_end_above: tick(10),
+    })
+}
+
+/// Next initialized tick from the pool's bitmap, clamped to the valid tick range
+fn search_tick(pool: &IUniswapV3Pool, tick: i32, tick_spacing: i32, lte: bool) -> Result<(i32, bool), Vec<u8>> {
+    let word_pos = bitmap_word_position(tick, tick_spacing, lte);
+    let word = pool.tick_bitmap(Call::new(), word_pos)
+        .map_err(|_| b"Pool tick bitmap unavailable".to_vec())?;
+
+    let (next, initialized) = next_initialized_tick_within_word(word, tick, tick_spacing, lte);
+    Ok((next.clamp(MIN_TICK, MAX_TICK), initialized))
+}
+
+/// liquidity_net of an initialized tick
+fn fetch_liquidity_net(pool: &IUniswapV3Pool, tick: i32) -> Result<i128, Vec<u8>> {
+    let tick = I24::try_from(tick).map_err(|_| b"Tick out of range".to_vec())?;
+    let info = pool.ticks(Call::new(), tick)
+        .map_err(|_| b"Pool tick unavailable".to_vec())?;
+    Ok(info.1)
+}
+
+/// Read a V3 pool's price, liquidity and adjacent ticks
+/// Reverting or non-conforming contracts are reported as errors
+pub fn fetch_state(pool_address: Address, token_a: Address, token_b: Address) -> Result<V3PoolState, Vec<u8>> {
+    let pool = IUniswapV3Pool::new(pool_address);
+
+    let token0 = pool.token_0(Call::new())
+        .map_err(|_| b"Pool token0 unavailable".to_vec())?;
+    let token1 = pool.token_1(Call::new())
+        .map_err(|_| b"Pool token1 unavailable".to_vec())?;
+    let token0_is_a = if token0 == token_a && token1 == token_b {
+        true
+    } else if token0 == token_b && token1 == token_a {
+        false
+    } else {
+        return Err(b"Pool tokens mismatch".to_vec());
+    };
+
+    let slot0 = pool.slot_0(Call::new())
+        .map_err(|_| b"Pool slot0 unavailable".to_vec())?;
+    let liquidity = pool.liquidity(Call::new())
+        .map_err(|_| b"Pool liquidity unavailable".to_vec())?;
+    let tick_spacing = pool.tick_spacing(Call::new())
+        .map_err(|_| b"Pool tick spacing unavailable".to_vec())?
+        .as_i32();
+
+    if tick_spacing <= 0 {
+        return Err(b"Invalid tick spacing".to_vec());
+    }
+
+    let sqrt_price_x96 = U256::from(slot0.0);
+    let tick = slot0.1.as_i32();
+
+    // token0 -> token1: search down from the current tick, then again past the crossed tick
+    let (tick_below, below_initialized) = search_tick(&pool, tick, tick_spacing, true)?;
+    let liquidity_net_below = if below_initialized { fetch_liquidity_net(&pool, tick_below)? } else { 0 };
+    let (range_end_below, _) = search_tick(&pool, tick_below - 1, tick_spacing, true)?;
+
+    // token1 -> token0: search up
+    let (tick_above, above_initialized) = search_tick(&pool, tick, tick_spacing, false)?;
+    let liquidity_net_above = if above_initialized { fetch_liquidity_net(&pool, tick_above)? } else { 0 };
+    let (range_end_above, _) = search_tick(&pool, tick_above, tick_spacing, false)?;
+
+    Ok(V3PoolState {
+        sqrt_price_x96,
+        tick,
+        tick_spacing,
+        liquidity: U256::from(liquidity),
+        token0_is_a,
+        tick_below,
+        liquidity_net_below,
+        range_end_below,
+        tick_above,
+        liquidity_net_above,
+        range_end_above,
+    })
+}
+
+/// Adapter for Uniswap V3 pools; exact-output quotes use the default search
+pub struct UniswapV3Adapter;
+
+impl PoolAdapter for UniswapV3Adapter {
+    fn pool_type(&self) -> u8 {
+        POOL_TYPE
+    }
+
+    /// Reserves are the active range's virtual reserves
+    fn fetch_state(&self, pool_address: Address, token_a: Address, token_b: Address) -> Result<PoolSnapshot, Vec<u8>> {
+        let state = fetch_state(pool_address, token_a, token_b)?;
+        let (reserve0, reserve1) = virtual_reserves(state.sqrt_price_x96, state.liquidity);
+        let (reserve_a, reserve_b) = if state.token0_is_a { (reserve0, reserve1) } else { (reserve1, reserve0) };
+        Ok(PoolSnapshot { reserve_a, reserve_b, state: encode_state(&state) })
+    }
+
+    fn quote_exact_in(&self, pool: &PoolView, a_to_b: bool, amount_in: U256) -> Result<U256, &'static str> {
+        let state = decode_state(pool.state)?;
+        quote_exact_in(&state, a_to_b == state.token0_is_a, amount_in, pool.fee_bps)
+    }
+
+    fn spot_price(&self, pool: &PoolView, a_to_b: bool) -> Result<U256, &'static str> {
+        let state = decode_state(pool.state)?;
+        spot_price(state.sqrt_price_x96, a_to_b == state.token0_is_a)
+    }
+
+    fn payment(&self) -> SwapPayment {
+        SwapPayment::Callback
+    }
+
+    /// pool.swap(recipient, zeroForOne, amount_in, price limit, ""); the pool collects token_in
+    /// through uniswapV3SwapCallback, and the caller checks min_amount_out against the deltas
+    fn encode_swap(&self, pool: &PoolView, swap: &SwapParams) -> Result<SwapCall, &'static str> {
+        let state = decode_state(pool.state)?;
+        let zero_for_one = swap.a_to_b == state.token0_is_a;
+
+        // Positive amountSpecified means exact input and must fit in int256
+        if swap.amount_in.bit(255) {
+            return Err("Amount too large");
+        }
+
+        // No price limit beyond the tick range
+        let sqrt_price_limit = if zero_for_one {
+            sqrt_ratio_at_tick(MIN_TICK)? + U256::from(1)
+        } else {
+            sqrt_ratio_at_tick(MAX_TICK)? - U256::from(1)
+        };
+
+        Ok(SwapCall {
+            target: pool.pool_address,
+            calldata: pool_adapter::encode_call("swap(address,bool,int256,uint160,bytes)", &[
+                pool_adapter::address_word(swap.recipient),
+                U256::from(zero_for_one as u8),
+                swap.amount_in,
+                sqrt_price_limit,
+                U256::from(160), // Offset of the empty callback data
+                U256::ZERO,
+            ]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIQUIDITY: u128 = 1_000_000_000_000_000_000_000; // 1e21
+
+    fn q96() -> U256 {
+        U256::from(1) << 96
+    }
+
+    fn state_at_tick_zero(liquidity_net_below: i128, liquidity_net_above: i128) -> V3PoolState {
+        V3PoolState {
+            sqrt_price_x96: q96(),
+            tick: 0,
+            tick_spacing: 60,
+            liquidity: U256::from(LIQUIDITY),
+            token0_is_a: true,
+            tick_below: -60,
+            liquidity_net_below,
+            range_end_below: -120,
+            tick_above: 60,
+            liquidity_net_above,
+            range_end_above: 120,
+        }
+    }
+
+    #[test]
+    fn test_sqrt_ratio_at_tick_known_values() {
+        assert_eq!(sqrt_ratio_at_tick(0).unwrap(), q96());
+        assert_eq!(sqrt_ratio_at_tick(MIN_TICK).unwrap(), U256::from(4295128739u64));
+        assert_eq!(
+            sqrt_ratio_at_tick(MAX_TICK).unwrap(),
+            U256::from_str_radix("1461446703485210103287273052203988822378723970342", 10).unwrap()
+        );
+        assert_eq!(
+            sqrt_ratio_at_tick(1).unwrap(),
+            U256::from_str_radix("79232123823359799118286999568", 10).unwrap()
+        );
+        assert_eq!(
+            sqrt_ratio_at_tick(-1).unwrap(),
+            U256::from_str_radix("79224201403219477170569942574", 10).unwrap()
+        );
+        assert!(sqrt_ratio_at_tick(MAX_TICK + 1).is_err());
+    }
+
+    #[test]
+    fn test_next_initialized_tick_within_word() {
+        // Spacing 1, bits set for ticks 2 and 78
+        let word = (U256::from(1) << 2) | (U256::from(1) << 78);
+
+        assert_eq!(next_initialized_tick_within_word(word, 78, 1, true), (78, true));
+        assert_eq!(next_initialized_tick_within_word(word, 77, 1, true), (2, true));
+        assert_eq!(next_initialized_tick_within_word(word, 1, 1, true), (0, false));
+        assert_eq!(next_initialized_tick_within_word(word, 2, 1, false), (78, true));
+        assert_eq!(next_initialized_tick_within_word(word, 78, 1, false), (255, false));
+
+        // Negative ticks compress toward negative infinity
+        assert_eq!(bitmap_word_position(-1, 1, true), -1);
+        assert_eq!(bitmap_word_position(-61, 60, true), -1);
+        assert_eq!(bitmap_word_position(255, 1, false), 1);
+    }
+
+    #[test]
+    fn test_quote_within_range_matches_constant_product() {
+        // Small swap at price 1 with no fee: output just below input
+        let state = state_at_tick_zero(0, 0);
+        let amount = U256::from(1_000_000_000_000_000u64); // 1e15, far from the next tick
+
+        let out0 = quote_exact_in(&state, true, amount, U256::ZERO).unwrap();
+        let out1 = quote_exact_in(&state, false, amount, U256::ZERO).unwrap();
+
+        // Constant product with virtual reserves L: out = a * L / (L + a)
+        let expected = amount * U256::from(LIQUIDITY) / (U256::from(LIQUIDITY) + amount);
+        assert!(expected - out0 <= U256::from(1));
+        assert!(expected - out1 <= U256::from(1));
+
+        // Fees reduce output
+        assert!(quote_exact_in(&state, true, amount, U256::from(30)).unwrap() < out0);
+    }
+
+    #[test]
+    fn test_quote_crosses_adjacent_tick() {
+        // 0.4% of L: passes tick -60 (about 0.3% of L) but stays short of -120
+        let amount = U256::from(LIQUIDITY / 250);
+
+        // Crossing -60 downward with liquidity_net = +L/2 halves liquidity
+        let thin = quote_exact_in(&state_at_tick_zero(LIQUIDITY as i128 / 2, 0), true, amount, U256::ZERO).unwrap();
+        let flat = quote_exact_in(&state_at_tick_zero(0, 0), true, amount, U256::ZERO).unwrap();
+        assert!(thin < flat);
+
+        // Removing more liquidity than is active is invalid
+        let broken = state_at_tick_zero(LIQUIDITY as i128 * 2, 0);
+        assert_eq!(quote_exact_in(&broken, true, amount, U256::ZERO), Err("Invalid liquidity net"));
+    }
+
+    #[test]
+    fn test_quote_beyond_known_range_rejected() {
+        let state = state_at_tick_zero(0, 0);
+        let amount = U256::from(LIQUIDITY / 10);
+        assert_eq!(quote_exact_in(&state, false, amount, U256::ZERO), Err("Swap exceeds known tick range"));
+    }
+
+    #[test]
+    fn test_state_words_round_trip() {
+        let state = state_at_tick_zero(-(LIQUIDITY as i128) / 3, LIQUIDITY as i128 / 5);
+        assert_eq!(decode_state(&encode_state(&state)), Ok(state));
+        assert_eq!(decode_state(&[U256::ZERO]), Err("Invalid pool state"));
+    }
+
+    #[test]
+    fn test_spot_price() {
+        let one = U256::from(PRICE_PRECISION);
+        assert_eq!(spot_price(q96(), true).unwrap(), one);
+        assert_eq!(spot_price(q96(), false).unwrap(), one);
+
+        // One tick up: token1 per token0 is 1.0001, token0 per token1 its inverse
+        let sqrt_price = sqrt_ratio_at_tick(1).unwrap();
+        let close = |price: U256, expected: u64| {
+            let expected = U256::from(expected);
+            (if price > expected { price - expected } else { expected - price }) < U256::from(10)
+        };
+        assert!(close(spot_price(sqrt_price, true).unwrap(), 1_000_100_000_000_000_000));
+        assert!(close(spot_price(sqrt_price, false).unwrap(), 999_900_009_999_000_100));
+    }
+
+    #[test]
+    fn test_exact_out_search_inverts_quote() {
+        let state = state_at_tick_zero(0, 0);
+        let pool = PoolView {
+            pool_address: Address::ZERO,
+            token_a: Address::ZERO,
+            token_b: Address::ZERO,
+            reserve_a: U256::ZERO,
+            reserve_b: U256::ZERO,
+            fee_bps: U256::from(30),
+            state: &encode_state(&state),
+        };
+
+        let amount_out = U256::from(1_000_000_000_000_000u64);
+        let amount_in = UniswapV3Adapter.quote_exact_out(&pool, true, amount_out).unwrap();
+        assert!(quote_exact_in(&state, true, amount_in, pool.fee_bps).unwrap() >= amount_out);
+        assert!(quote_exact_in(&state, true, amount_in - U256::from(1), pool.fee_bps).unwrap() < amount_out);
+    }
+
+    #[test]
+    fn test_virtual_reserves() {
+        let (reserve0, reserve1) = virtual_reserves(q96(), U256::from(LIQUIDITY));
+        assert_eq!(reserve0, U256::from(LIQUIDITY));
+        assert_eq!(reserve1, U256::from(LIQUIDITY));
+    }
+}